@@ -0,0 +1,64 @@
+// premultiplied alpha byte helpers, see premultiply_alpha in the quantizer
+// crate for why. the edge crate's blur uses them directly
+
+// true if any pixel is not fully opaque, i.e. premultiplying would change something
+pub fn has_translucency(data: &[u8]) -> bool {
+    data.chunks_exact(4).any(|px| px[3] < 255)
+}
+
+// c * a / 255, rounded
+pub fn premultiply_pixels(data: &mut [u8]) {
+    for px in data.chunks_exact_mut(4) {
+        let a = px[3] as u32;
+        for value in px.iter_mut().take(3) {
+            *value = ((*value as u32 * a + 127) / 255) as u8;
+        }
+    }
+}
+
+// c * 255 / a, rounded and capped at 255 (a premultiplied channel above alpha
+// isn't valid, but the bytes can come from anywhere)
+pub fn unpremultiply_pixels(data: &mut [u8]) {
+    for px in data.chunks_exact_mut(4) {
+        let a = px[3] as u32;
+        if a == 0 {
+            px[..3].fill(0);
+            continue;
+        }
+        for value in px.iter_mut().take(3) {
+            *value = ((*value as u32 * 255 + a / 2) / a).min(255) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(value: u8, alpha: u8) -> u8 {
+        let mut px = [value, value, value, alpha];
+        premultiply_pixels(&mut px);
+        unpremultiply_pixels(&mut px);
+        assert_eq!(px[3], alpha);
+        px[0]
+    }
+
+    #[test]
+    fn round_trip_is_exact_when_opaque() {
+        for value in 0..=255 {
+            assert_eq!(round_trip(value, 255), value);
+        }
+    }
+
+    #[test]
+    fn round_trip_stays_within_255_over_alpha() {
+        for alpha in 1..255u8 {
+            for value in 0..=255u8 {
+                let diff = (round_trip(value, alpha) as f64 - value as f64).abs();
+                assert!(diff < 255.0 / alpha as f64, "{} at alpha {} came back {} off", value, alpha, diff);
+            }
+        }
+        // nothing to recover at alpha 0
+        assert_eq!(round_trip(200, 0), 0);
+    }
+}
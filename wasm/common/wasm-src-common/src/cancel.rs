@@ -0,0 +1,58 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{error, ErrorCode};
+
+// lets JS stop a long filter early, e.g. when a slider moved again before the
+// last result came back:
+//
+//   token?.cancel();
+//   token = new CancellationToken();
+//   const out = await quantize_async_cancellable(img, k, 64, null, token);  // rejects with CANCELLED
+//
+// a synchronous call holds its thread until it returns, so cancel() can only
+// land in between if it comes from somewhere else: the chunk boundaries of
+// quantize_async, or another thread of a `parallel` build. filters that take a
+// token check it every CHECK_ROWS rows and between k-means iterations, and a
+// tripped token always ends in a CANCELLED error, never in a half written image.
+// those are separate `*_cancellable` exports, see quantize_cancellable for why
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+#[wasm_bindgen]
+impl CancellationToken {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    // can't be undone, make a new token for the next run
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+// rows between two looks at the token
+pub const CHECK_ROWS: usize = 16;
+
+// for loops that just stop early, the caller turns that into an error with `check`
+pub fn is_cancelled(token: Option<&CancellationToken>) -> bool {
+    token.is_some_and(|token| token.cancelled())
+}
+
+pub fn check(token: Option<&CancellationToken>) -> Result<(), JsValue> {
+    if is_cancelled(token) {
+        return Err(error(ErrorCode::Cancelled, "operation was cancelled"));
+    }
+    Ok(())
+}
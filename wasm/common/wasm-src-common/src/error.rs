@@ -0,0 +1,56 @@
+use wasm_bindgen::prelude::*;
+
+// validation errors reach JS as an ImageError with a `code` and a `message`,
+// so callers can switch on err.code instead of matching message strings.
+// errors from the browser itself (e.g. the ImageData constructor) still come
+// through as whatever JS threw
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    InvalidDimensions = "INVALID_DIMENSIONS",
+    InvalidK = "INVALID_K",
+    InvalidParameter = "INVALID_PARAMETER",
+    InvalidMode = "INVALID_MODE",
+    InvalidLength = "INVALID_LENGTH",
+    OutOfBounds = "OUT_OF_BOUNDS",
+    EncodeError = "ENCODE_ERROR",
+    DecodeError = "DECODE_ERROR",
+    UnsupportedFormat = "UNSUPPORTED_FORMAT",
+    Cancelled = "CANCELLED",
+    ParseError = "PARSE_ERROR",
+}
+
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct ImageError {
+    code: ErrorCode,
+    message: String,
+}
+
+#[wasm_bindgen]
+impl ImageError {
+    #[wasm_bindgen(getter)]
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+// plain rust constructor for code that has to run (and be tested) without a
+// JS host, it becomes a JsValue at the export boundary
+impl ImageError {
+    pub fn new(code: ErrorCode, message: &str) -> ImageError {
+        ImageError {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+pub fn error(code: ErrorCode, message: &str) -> JsValue {
+    ImageError::new(code, message).into()
+}
@@ -0,0 +1,257 @@
+// the k-means core behind the quantizer, shared with the edge crate's pipeline.
+// deterministic init and a 1000 pixel training sample, see the quantizer crate
+// for the exports built on top
+
+use crate::cancel::{self, CancellationToken};
+use crate::nearest::NearestCentroid;
+#[cfg(feature = "parallel")]
+use crate::parallel;
+
+// buffer level k-means quantize, what the quantizer's `quantize` export and the
+// edge crate's pipeline both run
+// `output` must be the same length as `data`
+pub fn quantize_into(data: &[u8], output: &mut [u8], k: usize) {
+    let pixels = rgb_pixels(data);
+    let centroids = train_centroids(&pixels, k);
+    remap_into(data, &pixels, &centroids, output);
+}
+
+// mapping all pixels to nearest centroid color, alpha copied from `data`
+pub fn remap_into(data: &[u8], pixels: &[[f64; 3]], centroids: &[[f64; 3]], output: &mut [u8]) {
+    #[cfg(feature = "parallel")]
+    if parallel::pool_ready() {
+        use rayon::prelude::*;
+        // every chunk gets its own lookup, the color cache isn't shared
+        let chunk = parallel::CHUNK_PIXELS;
+        output
+            .par_chunks_mut(chunk * 4)
+            .zip(data.par_chunks(chunk * 4))
+            .zip(pixels.par_chunks(chunk))
+            .for_each(|((output, data), pixels)| remap_chunk(data, pixels, centroids, output));
+        return;
+    }
+
+    remap_chunk(data, pixels, centroids, output);
+}
+
+pub fn remap_chunk(data: &[u8], pixels: &[[f64; 3]], centroids: &[[f64; 3]], output: &mut [u8]) {
+    let mut index = NearestCentroid::new(centroids);
+    for (i, pixel) in pixels.iter().enumerate() {
+        let nearest = index.find(pixel);
+        let [r, g, b] = centroids[nearest];
+        
+        output[i * 4] = r.round() as u8;
+        output[i * 4 + 1] = g.round() as u8;
+        output[i * 4 + 2] = b.round() as u8;
+        output[i * 4 + 3] = data[i * 4 + 3];
+    }
+}
+
+// grab all RGB values (skip alpha channel)
+pub fn rgb_pixels(data: &[u8]) -> Vec<[f64; 3]> {
+    let mut pixels: Vec<[f64; 3]> = Vec::with_capacity(data.len() / 4);
+    for i in (0..data.len()).step_by(4) {
+        pixels.push([
+            data[i] as f64,
+            data[i + 1] as f64,
+            data[i + 2] as f64,
+        ]);
+    }
+    pixels
+}
+
+pub fn train_centroids(pixels: &[[f64; 3]], k: usize) -> Vec<[f64; 3]> {
+    train_centroids_with(pixels, k, |_, _, _| {})
+}
+
+// stops between iterations once `cancel` is tripped, the caller has to check
+// the token afterwards, what comes back then is only partly trained
+pub fn train_centroids_cancellable(pixels: &[[f64; 3]], k: usize, cancel: Option<&CancellationToken>) -> Vec<[f64; 3]> {
+    let sampled_pixels = deterministic_sample(pixels, 1000.min(pixels.len()));
+    let centroids = initialize_centroids_deterministic(&sampled_pixels, k);
+    refine_centroids_cancellable(&sampled_pixels, centroids, |_, _, _| {}, cancel)
+}
+
+// `on_iteration(iteration, delta, centroids)` runs after every iteration with the
+// updated centroids, before the convergence check
+pub fn train_centroids_with(
+    pixels: &[[f64; 3]],
+    k: usize,
+    on_iteration: impl FnMut(usize, f64, &[[f64; 3]]),
+) -> Vec<[f64; 3]> {
+    // training on full image is too slow. sampling 1k pixels instead
+    let sample_size = 1000.min(pixels.len());
+    let sampled_pixels = deterministic_sample(pixels, sample_size);
+    
+    let centroids = initialize_centroids_deterministic(&sampled_pixels, k);
+    refine_centroids(&sampled_pixels, centroids, on_iteration)
+}
+
+// the k-means loop itself, starting from whatever centroids it's given
+pub fn refine_centroids(
+    sampled_pixels: &[[f64; 3]],
+    centroids: Vec<[f64; 3]>,
+    on_iteration: impl FnMut(usize, f64, &[[f64; 3]]),
+) -> Vec<[f64; 3]> {
+    refine_centroids_cancellable(sampled_pixels, centroids, on_iteration, None)
+}
+
+pub fn refine_centroids_cancellable(
+    sampled_pixels: &[[f64; 3]],
+    mut centroids: Vec<[f64; 3]>,
+    mut on_iteration: impl FnMut(usize, f64, &[[f64; 3]]),
+    cancel: Option<&CancellationToken>,
+) -> Vec<[f64; 3]> {
+    let k = centroids.len();
+    let max_iterations = 20; // usually converges way before this
+    for iteration in 1..=max_iterations {
+        if cancel::is_cancelled(cancel) {
+            break;
+        }
+        let mut clusters: Vec<Vec<[f64; 3]>> = vec![Vec::new(); k];
+        
+        // clusters are filled in pixel order either way, so the means come
+        // out the same with or without threads
+        for (pixel, nearest) in sampled_pixels.iter().zip(nearest_labels(sampled_pixels, &centroids)) {
+            clusters[nearest].push(*pixel);
+        }
+        
+        let new_centroids: Vec<[f64; 3]> = clusters
+            .iter()
+            .enumerate()
+            .map(|(i, cluster)| {
+                if cluster.is_empty() {
+                    centroids[i]
+                } else {
+                    calculate_mean(cluster)
+                }
+            })
+            .collect();
+
+        let delta = centroids
+            .iter()
+            .zip(new_centroids.iter())
+            .map(|(old, new)| euclidean_distance(old, new))
+            .fold(0.0, f64::max);
+        on_iteration(iteration, delta, &new_centroids);
+        
+        if centroids_converged(&centroids, &new_centroids, 1.0) {
+            break;
+        }
+        centroids = new_centroids;
+    }
+
+    centroids
+}
+
+// nearest centroid index for every pixel, spread over the thread pool when there is one
+pub fn nearest_labels(pixels: &[[f64; 3]], centroids: &[[f64; 3]]) -> Vec<usize> {
+    #[cfg(feature = "parallel")]
+    if parallel::pool_ready() {
+        use rayon::prelude::*;
+        return pixels.par_iter().map(|p| find_nearest_centroid(p, centroids)).collect();
+    }
+
+    pixels.iter().map(|p| find_nearest_centroid(p, centroids)).collect()
+}
+
+// picks evenly spaced pixels for sampling
+pub fn deterministic_sample(pixels: &[[f64; 3]], sample_size: usize) -> Vec<[f64; 3]> {
+    let mut sampled = Vec::with_capacity(sample_size);
+    let step = pixels.len() as f64 / sample_size as f64;
+    
+    for i in 0..sample_size {
+        let idx = (i as f64 * step).floor() as usize;
+        sampled.push(pixels[idx]);
+    }
+    
+    sampled
+}
+
+// spreads initial centroids out for faster convergence 
+pub fn initialize_centroids_deterministic(pixels: &[[f64; 3]], k: usize) -> Vec<[f64; 3]> {
+    if pixels.is_empty() {
+        return Vec::new();
+    }
+    
+    let mut centroids = Vec::with_capacity(k);
+    
+    // start with pixel at 1/4 mark (stable middle-ish color)
+    centroids.push(pixels[pixels.len() / 4]);
+    
+    // pick remaining centroids far apart from each other
+    for _ in 1..k {
+        let mut max_min_dist = -1.0;
+        let mut best_pixel_idx = 0;
+        
+        // don't check every single pixel. too slow        
+        let sample_rate = 1.max(pixels.len() / 1000);
+        
+        for i in (0..pixels.len()).step_by(sample_rate) {
+            let pixel = &pixels[i];
+            
+            // find closest existing centroid
+            let mut min_dist = f64::INFINITY;
+            for centroid in &centroids {
+                let dist = euclidean_distance(pixel, centroid);
+                if dist < min_dist {
+                    min_dist = dist;
+                }
+            }
+            
+            // track furthest pixel
+            if min_dist > max_min_dist {
+                max_min_dist = min_dist;
+                best_pixel_idx = i;
+            }
+        }
+        
+        centroids.push(pixels[best_pixel_idx]);
+    }
+    
+    centroids
+}
+
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+pub use crate::simd::find_nearest_centroid;
+
+#[cfg(not(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128")))]
+pub fn find_nearest_centroid(pixel: &[f64; 3], centroids: &[[f64; 3]]) -> usize {
+    let mut min_dist = f64::INFINITY;
+    let mut nearest = 0;
+    
+    for (i, centroid) in centroids.iter().enumerate() {
+        let dist = euclidean_distance(pixel, centroid);
+        if dist < min_dist {
+            min_dist = dist;
+            nearest = i;
+        }
+    }
+    
+    nearest
+}
+
+pub fn euclidean_distance(p1: &[f64; 3], p2: &[f64; 3]) -> f64 {
+    let dr = p1[0] - p2[0];
+    let dg = p1[1] - p2[1];
+    let db = p1[2] - p2[2];
+    (dr * dr + dg * dg + db * db).sqrt()
+}
+
+pub fn calculate_mean(cluster: &[[f64; 3]]) -> [f64; 3] {
+    let len = cluster.len() as f64;
+    let sum = cluster.iter().fold([0.0, 0.0, 0.0], |acc, pixel| {
+        [acc[0] + pixel[0], acc[1] + pixel[1], acc[2] + pixel[2]]
+    });
+    
+    [sum[0] / len, sum[1] / len, sum[2] / len]
+}
+
+pub fn centroids_converged(old: &[[f64; 3]], new: &[[f64; 3]], threshold: f64) -> bool {
+    for (old_c, new_c) in old.iter().zip(new.iter()) {
+        if euclidean_distance(old_c, new_c) > threshold {
+            return false;
+        }
+    }
+    true
+}
\ No newline at end of file
@@ -0,0 +1,31 @@
+// shared internals of the filter, quantizer and edge detection modules: the
+// k-means core, error type, cancellation and pixel helpers. the only function
+// exported to JS from here is `simd_supported` below (plus the ImageError /
+// CancellationToken classes the modules hand out and init_thread_pool in
+// `parallel` builds), so a module depending on this only carries what it uses
+
+pub mod alpha;
+pub mod cancel;
+pub mod error;
+pub mod kmeans;
+pub mod linear;
+pub mod nearest;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+mod simd;
+
+use wasm_bindgen::prelude::*;
+
+// simd128 fast paths. wasm can't detect features at runtime (a module that
+// uses simd instructions doesn't even load on an engine without them), so the
+// choice is made at build time:
+//   RUSTFLAGS="-C target-feature=+simd128" wasm-pack build -- --features simd
+// every fast path gives exactly the same results as the scalar code it replaces.
+//
+// each module's `simd` feature also turns on this crate's, so every wasm module
+// gets this probe once and it answers for that module's own build
+#[wasm_bindgen]
+pub fn simd_supported() -> bool {
+    cfg!(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))
+}
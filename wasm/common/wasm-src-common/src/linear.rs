@@ -0,0 +1,31 @@
+// linear light helpers, used by all three modules. sRGB bytes are gamma
+// encoded, so averaging them directly comes out too dark wherever different
+// colors mix: a red/green edge blurs to a muddy (128, 128, 0) instead of the
+// (188, 188, 0) the light actually adds up to. decode, average, encode fixes it
+
+// linear light 0..1 for every sRGB byte
+pub fn decode_lut() -> [f64; 256] {
+    std::array::from_fn(|i| srgb_to_linear(i as f64 / 255.0))
+}
+
+// linear light 0..1 back to an sRGB byte, out of range values clamp
+pub fn encode(v: f64) -> u8 {
+    (linear_to_srgb(v.clamp(0.0, 1.0)) * 255.0).round() as u8
+}
+
+// sRGB transfer function, 0..1 in and out
+pub fn srgb_to_linear(v: f64) -> f64 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(v: f64) -> f64 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use crate::kmeans::{euclidean_distance, find_nearest_centroid};
+
+// from here on the brute force scan over every centroid starts to dominate
+// remapping, below it the bookkeeping costs more than it saves
+const ACCELERATE_K: usize = 64;
+
+// drop-in for calling `find_nearest_centroid` on every pixel of an image,
+// same answers (lowest index on ties) but faster for big palettes:
+//   - triangle inequality pruning: guess a centroid g (the previous pixel's
+//     answer, neighbours are usually alike) at distance d. any centroid more
+//     than 2d from g is further than d from the pixel, so the search walks g's
+//     other centroids nearest first and stops at the first one past 2d
+//   - a cache keyed on the exact rgb, so flat art and screenshots with a few
+//     thousand distinct colors only search once per color
+// pixels have to be whole 0..255 values like `rgb_pixels` gives
+pub struct NearestCentroid<'a> {
+    centroids: &'a [[f64; 3]],
+    // per centroid, every other centroid as (distance, index) nearest first.
+    // empty when k is below ACCELERATE_K
+    neighbours: Vec<Vec<(f64, usize)>>,
+    cache: HashMap<u32, usize>,
+    guess: usize,
+}
+
+impl<'a> NearestCentroid<'a> {
+    pub fn new(centroids: &'a [[f64; 3]]) -> NearestCentroid<'a> {
+        let neighbours = if centroids.len() < ACCELERATE_K {
+            Vec::new()
+        } else {
+            centroids
+                .iter()
+                .enumerate()
+                .map(|(i, a)| {
+                    let mut list: Vec<(f64, usize)> = centroids
+                        .iter()
+                        .enumerate()
+                        .filter(|&(j, _)| j != i)
+                        .map(|(j, b)| (euclidean_distance(a, b), j))
+                        .collect();
+                    list.sort_by(|x, y| x.0.total_cmp(&y.0));
+                    list
+                })
+                .collect()
+        };
+        NearestCentroid { centroids, neighbours, cache: HashMap::new(), guess: 0 }
+    }
+
+    pub fn find(&mut self, pixel: &[f64; 3]) -> usize {
+        if self.neighbours.is_empty() {
+            return find_nearest_centroid(pixel, self.centroids);
+        }
+
+        let key = (pixel[0] as u32) << 16 | (pixel[1] as u32) << 8 | pixel[2] as u32;
+        if let Some(&nearest) = self.cache.get(&key) {
+            return nearest;
+        }
+        let nearest = self.pruned(pixel);
+        self.cache.insert(key, nearest);
+        self.guess = nearest;
+        nearest
+    }
+
+    fn pruned(&self, pixel: &[f64; 3]) -> usize {
+        let guess_dist = euclidean_distance(pixel, &self.centroids[self.guess]);
+        // the margin covers float rounding in the distances, so a centroid the
+        // brute force scan would pick by a hair is never cut off
+        let bound = 2.0 * guess_dist * (1.0 + 1e-12) + 1e-9;
+
+        // visiting order isn't index order, so ties are settled by index
+        // explicitly to match the brute force scan
+        let mut best = (guess_dist, self.guess);
+        for &(between, i) in &self.neighbours[self.guess] {
+            if between > bound {
+                break;
+            }
+            let dist = euclidean_distance(pixel, &self.centroids[i]);
+            if dist < best.0 || (dist == best.0 && i < best.1) {
+                best = (dist, i);
+            }
+        }
+
+        best.1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // small lcg so the test doesn't need a rand dependency
+    fn lcg(state: &mut u32) -> u32 {
+        *state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+        *state >> 8
+    }
+
+    #[test]
+    fn matches_brute_force_at_k_128() {
+        let mut state = 7;
+        let mut centroids: Vec<[f64; 3]> = (0..128)
+            .map(|_| {
+                [
+                    (lcg(&mut state) % 256) as f64 + 0.5,
+                    (lcg(&mut state) % 256) as f64 + 0.25,
+                    (lcg(&mut state) % 256) as f64,
+                ]
+            })
+            .collect();
+        // a duplicate makes sure ties still go to the lowest index
+        centroids[100] = centroids[3];
+
+        // gradient with noise, so neighbouring pixels are alike but not equal
+        let (width, height) = (96, 64);
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let noise = (lcg(&mut state) % 24) as usize;
+                pixels.push([
+                    ((x * 255 / width + noise) % 256) as f64,
+                    ((y * 255 / height + noise) % 256) as f64,
+                    ((x + y + noise * 3) % 256) as f64,
+                ]);
+            }
+        }
+        pixels.push(centroids[3].map(f64::floor));
+
+        let mut nearest = NearestCentroid::new(&centroids);
+        for pixel in &pixels {
+            assert_eq!(nearest.find(pixel), find_nearest_centroid(pixel, &centroids), "pixel {:?}", pixel);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use js_sys::Promise;
+use wasm_bindgen::prelude::*;
+
+// web worker thread pool through wasm-bindgen-rayon, only in builds with the
+// `parallel` feature. workers sharing the wasm memory need SharedArrayBuffer,
+// which browsers only give to cross-origin isolated pages (COOP/COEP headers),
+// and the wasm has to be built with atomics on nightly, see the
+// wasm-bindgen-rayon readme for the flags.
+//
+//   await init_thread_pool(navigator.hardwareConcurrency);
+//
+// until that promise resolves every function runs on the calling thread
+// exactly like a build without the feature. the threaded paths split work so
+// that nothing depends on the order threads finish in, the output is the same
+// bytes either way. the quantizer and the edge crate both run on this pool,
+// each module gets its own copy of the export
+
+// rows / pixels handed to a thread at a time, small enough to balance, big
+// enough that scheduling doesn't show up
+pub const CHUNK_PIXELS: usize = 16 * 1024;
+
+static POOL_READY: AtomicBool = AtomicBool::new(false);
+
+#[wasm_bindgen]
+pub fn init_thread_pool(threads: usize) -> Promise {
+    let ready = Closure::once(|_: JsValue| POOL_READY.store(true, Ordering::Release));
+    let promise = wasm_bindgen_rayon::init_thread_pool(threads).then(&ready);
+    // runs once when the pool is up, nothing to free it from after that
+    ready.forget();
+    promise
+}
+
+// true once `init_thread_pool` has finished, before that rayon has no workers
+// to run on and the single threaded paths have to be used
+pub fn pool_ready() -> bool {
+    POOL_READY.load(Ordering::Acquire)
+}
@@ -0,0 +1,47 @@
+// simd128 version of the nearest centroid scan, on with the `simd` feature
+// and RUSTFLAGS="-C target-feature=+simd128", see simd.rs in the quantizer
+
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+mod fast {
+    use std::arch::wasm32::*;
+
+    use crate::kmeans::euclidean_distance;
+
+    // two centroids per op. every lane does the same subtract, square, add in
+    // the same order and the same correctly rounded sqrt as
+    // `euclidean_distance`, and lanes are compared in index order, so the
+    // winner (lowest index on ties) is identical to the scalar scan
+    pub fn find_nearest_centroid(pixel: &[f64; 3], centroids: &[[f64; 3]]) -> usize {
+        let (pr, pg, pb) = (f64x2_splat(pixel[0]), f64x2_splat(pixel[1]), f64x2_splat(pixel[2]));
+        let mut min_dist = f64::INFINITY;
+        let mut nearest = 0;
+
+        let mut pairs = centroids.chunks_exact(2);
+        for (pair_index, pair) in (&mut pairs).enumerate() {
+            let (a, b) = (pair[0], pair[1]);
+            let dr = f64x2_sub(pr, f64x2(a[0], b[0]));
+            let dg = f64x2_sub(pg, f64x2(a[1], b[1]));
+            let db = f64x2_sub(pb, f64x2(a[2], b[2]));
+            let sum = f64x2_add(f64x2_add(f64x2_mul(dr, dr), f64x2_mul(dg, dg)), f64x2_mul(db, db));
+            let dist = f64x2_sqrt(sum);
+
+            for (lane, d) in [f64x2_extract_lane::<0>(dist), f64x2_extract_lane::<1>(dist)].into_iter().enumerate() {
+                if d < min_dist {
+                    min_dist = d;
+                    nearest = pair_index * 2 + lane;
+                }
+            }
+        }
+
+        if let [last] = pairs.remainder() {
+            if euclidean_distance(pixel, last) < min_dist {
+                nearest = centroids.len() - 1;
+            }
+        }
+
+        nearest
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+pub use fast::*;
@@ -0,0 +1,63 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+// renders the image as text, one character per cell. charset is a ramp from
+// darkest to lightest (e.g. "@%#*+=-:. "), invert flips it for light text on a
+// dark background. rows = cols * height / width / 2 since characters are about
+// twice as tall as they are wide
+#[wasm_bindgen]
+pub fn to_ascii(image_data: &ImageData, cols: u32, charset: &str, invert: bool) -> Result<String, JsValue> {
+    if cols == 0 {
+        return Err(error(ErrorCode::InvalidParameter, "cols must be at least 1"));
+    }
+    // chars(), not bytes, so multi-byte ramps like "█▓▒░ " work
+    let mut ramp: Vec<char> = charset.chars().collect();
+    if ramp.is_empty() {
+        return Err(error(ErrorCode::InvalidLength, "charset must not be empty"));
+    }
+    if invert {
+        ramp.reverse();
+    }
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    if width == 0 || height == 0 {
+        return Ok(String::new());
+    }
+    let data = image_data.data();
+
+    let cols = cols as usize;
+    let rows = ((cols * height) as f64 / width as f64 / 2.0).round().max(1.0) as usize;
+
+    // cell i covers pixels [i * size / cells, (i + 1) * size / cells), at least one
+    let span = |i: usize, cells: usize, size: usize| {
+        let start = (i * size / cells).min(size - 1);
+        let end = ((i + 1) * size / cells).max(start + 1);
+        (start, end)
+    };
+
+    let mut text = String::with_capacity(rows * (cols + 1));
+    for row in 0..rows {
+        let (y0, y1) = span(row, rows, height);
+        for col in 0..cols {
+            let (x0, x1) = span(col, cols, width);
+
+            let mut sum = 0f64;
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let idx = (y * width + x) * 4;
+                    // rec.601 luma
+                    sum += 0.299 * data[idx] as f64 + 0.587 * data[idx + 1] as f64 + 0.114 * data[idx + 2] as f64;
+                }
+            }
+            let luma = sum / ((x1 - x0) * (y1 - y0)) as f64;
+
+            let index = ((luma / 255.0) * ramp.len() as f64) as usize;
+            text.push(ramp[index.min(ramp.len() - 1)]);
+        }
+        text.push('\n');
+    }
+
+    Ok(text)
+}
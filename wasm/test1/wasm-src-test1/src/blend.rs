@@ -0,0 +1,192 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+// separable blend modes, B(backdrop, source) per channel on 0..1 values
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    Normal = 0,     // source
+    Multiply = 1,   // b * s, always darker
+    Screen = 2,     // 1 - (1 - b)(1 - s), always lighter
+    Overlay = 3,    // multiply in the backdrop's shadows, screen in its highlights
+    Darken = 4,     // min(b, s)
+    Lighten = 5,    // max(b, s)
+    Difference = 6, // |b - s|
+    Add = 7,        // min(b + s, 1), aka linear dodge
+}
+
+impl BlendMode {
+    pub(crate) fn apply(self, b: f32, s: f32) -> f32 {
+        match self {
+            BlendMode::Normal => s,
+            BlendMode::Multiply => b * s,
+            BlendMode::Screen => b + s - b * s,
+            BlendMode::Overlay => {
+                if b <= 0.5 {
+                    2.0 * b * s
+                } else {
+                    1.0 - 2.0 * (1.0 - b) * (1.0 - s)
+                }
+            }
+            BlendMode::Darken => b.min(s),
+            BlendMode::Lighten => b.max(s),
+            BlendMode::Difference => (b - s).abs(),
+            BlendMode::Add => (b + s).min(1.0),
+        }
+    }
+}
+
+// blends `overlay` onto `base`, both must be the same size. opacity (0..1)
+// scales the overlay's alpha
+#[wasm_bindgen]
+pub fn blend(base: &ImageData, overlay: &ImageData, mode: BlendMode, opacity: f32) -> Result<ImageData, JsValue> {
+    if base.width() != overlay.width() || base.height() != overlay.height() {
+        return Err(error(ErrorCode::InvalidDimensions, "images must have the same dimensions"));
+    }
+    if !(0.0..=1.0).contains(&opacity) {
+        return Err(error(ErrorCode::InvalidParameter, "opacity must be between 0 and 1"));
+    }
+
+    let mut data = base.data().0;
+    let top = overlay.data();
+
+    for (dst, src) in data.chunks_exact_mut(4).zip(top.chunks_exact(4)) {
+        let out = composite_pixel(
+            [dst[0], dst[1], dst[2], dst[3]],
+            [src[0], src[1], src[2]],
+            src[3] as f32 / 255.0 * opacity,
+            mode,
+        );
+        dst.copy_from_slice(&out);
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        base.width(),
+        base.height(),
+    )
+}
+
+// `blend` with the mode as a plain number for quick layering from JS:
+//   0 = over (normal), 1 = multiply, 2 = screen, 3 = add, 4 = difference
+// both alphas are respected, the overlay goes "over" the base in every mode
+#[wasm_bindgen]
+pub fn composite(base: &ImageData, overlay: &ImageData, mode: u8, opacity: f64) -> Result<ImageData, JsValue> {
+    let mode = match mode {
+        0 => BlendMode::Normal,
+        1 => BlendMode::Multiply,
+        2 => BlendMode::Screen,
+        3 => BlendMode::Add,
+        4 => BlendMode::Difference,
+        _ => return Err(error(ErrorCode::InvalidMode, "unknown composite mode, expected 0-4")),
+    };
+    blend(base, overlay, mode, opacity as f32)
+}
+
+// applies a filter only where the mask allows: each pixel (all four channels)
+// is lerp(base, filtered, m) with m taken from the mask's luminance, or from
+// its alpha with use_alpha. white / opaque = fully filtered. run the filter on
+// a copy first, e.g. apply_masked(img, gaussian_blur(img, 4), backgroundMask, false)
+#[wasm_bindgen]
+pub fn apply_masked(base: &ImageData, filtered: &ImageData, mask: &ImageData, use_alpha: bool) -> Result<ImageData, JsValue> {
+    let (width, height) = (base.width(), base.height());
+    if filtered.width() != width || filtered.height() != height || mask.width() != width || mask.height() != height {
+        return Err(error(ErrorCode::InvalidDimensions, "images must have the same dimensions"));
+    }
+
+    let mut data = base.data().0;
+    let top = filtered.data();
+    let mask = mask.data();
+
+    for ((dst, src), m) in data.chunks_exact_mut(4).zip(top.chunks_exact(4)).zip(mask.chunks_exact(4)) {
+        let t = if use_alpha {
+            m[3] as f32 / 255.0
+        } else {
+            // rec.601 luma
+            (0.299 * m[0] as f32 + 0.587 * m[1] as f32 + 0.114 * m[2] as f32) / 255.0
+        };
+        for (d, &s) in dst.iter_mut().zip(src) {
+            let b = *d as f32;
+            *d = (b + (s as f32 - b) * t).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&data), width, height)
+}
+
+// flat color wash over the whole image. mode uses the BlendMode numbering
+// (0 normal, 1 multiply, 2 screen, 3 overlay, 4 darken, 5 lighten,
+// 6 difference, 7 add), result = lerp(pixel, B(pixel, color), opacity). alpha is kept
+#[wasm_bindgen]
+pub fn blend_color(image_data: &ImageData, color: &[u8], opacity: f64, mode: u8) -> Result<ImageData, JsValue> {
+    if color.len() != 3 {
+        return Err(error(ErrorCode::InvalidLength, "color must be an rgb triple"));
+    }
+    if !(0.0..=1.0).contains(&opacity) {
+        return Err(error(ErrorCode::InvalidParameter, "opacity must be between 0 and 1"));
+    }
+    let mode = match mode {
+        0 => BlendMode::Normal,
+        1 => BlendMode::Multiply,
+        2 => BlendMode::Screen,
+        3 => BlendMode::Overlay,
+        4 => BlendMode::Darken,
+        5 => BlendMode::Lighten,
+        6 => BlendMode::Difference,
+        7 => BlendMode::Add,
+        _ => return Err(error(ErrorCode::InvalidMode, "unknown blend mode, expected 0-7")),
+    };
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let mut data = image_data.data().0;
+
+    // the source is constant, so each channel only has 256 possible results
+    let opacity = opacity as f32;
+    let luts: Vec<[u8; 256]> = color
+        .iter()
+        .map(|&s| {
+            let s = s as f32 / 255.0;
+            let mut lut = [0u8; 256];
+            for (i, v) in lut.iter_mut().enumerate() {
+                let b = i as f32 / 255.0;
+                let out = b + (mode.apply(b, s) - b) * opacity;
+                *v = (out * 255.0).round().clamp(0.0, 255.0) as u8;
+            }
+            lut
+        })
+        .collect();
+
+    for px in data.chunks_exact_mut(4) {
+        for (value, lut) in px.iter_mut().zip(&luts) {
+            *value = lut[*value as usize];
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&data), width, height)
+}
+
+// porter-duff "over" on straight (non-premultiplied) alpha, which is what
+// canvas ImageData holds. the blend result is only used where the backdrop is
+// opaque, over transparent backdrop the plain source shows through (W3C
+// compositing spec)
+pub(crate) fn composite_pixel(backdrop: [u8; 4], source: [u8; 3], source_alpha: f32, mode: BlendMode) -> [u8; 4] {
+    let ab = backdrop[3] as f32 / 255.0;
+    let a_s = source_alpha;
+    let ao = a_s + ab * (1.0 - a_s);
+    if ao <= 0.0 {
+        return [0, 0, 0, 0];
+    }
+
+    let mut out = [0u8; 4];
+    for c in 0..3 {
+        let cb = backdrop[c] as f32 / 255.0;
+        let cs = source[c] as f32 / 255.0;
+        let mixed = (1.0 - ab) * cs + ab * mode.apply(cb, cs);
+        let co = (a_s * mixed + (1.0 - a_s) * ab * cb) / ao;
+        out[c] = (co * 255.0).round().clamp(0.0, 255.0) as u8;
+    }
+    out[3] = (ao * 255.0).round().clamp(0.0, 255.0) as u8;
+    out
+}
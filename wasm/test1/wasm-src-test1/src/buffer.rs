@@ -0,0 +1,123 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use js_sys::{Uint8Array, WebAssembly};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_src_common::error::{error, ErrorCode, ImageError};
+
+use crate::invert_pixels;
+
+// zero-copy path for big images. an ImageData call copies the pixels into wasm
+// and the result back out, here JS writes straight into wasm memory instead:
+//
+//   const len = width * height * 4;
+//   const ptr = alloc_buffer(len);
+//   buffer_view(ptr, len).set(imageData.data);
+//   invert_in_place(ptr, len, width, height);
+//   ctx.putImageData(new ImageData(new Uint8ClampedArray(buffer_view(ptr, len)), width, height), 0, 0);
+//   free_buffer(ptr);
+//
+// anything that allocates inside wasm can grow the memory, and growing
+// detaches every view made before (they suddenly have length 0). so don't keep
+// a view around, call buffer_view again after each wasm call. the pointer
+// itself stays valid until free_buffer
+
+thread_local! {
+    // live buffers by address. the boxes own the memory, the map only moves
+    // the box pointers around, so the addresses JS holds don't change
+    static BUFFERS: RefCell<HashMap<u32, Box<[u8]>>> = RefCell::new(HashMap::new());
+}
+
+// zeroed buffer of `len` bytes, returns its address in wasm memory
+#[wasm_bindgen]
+pub fn alloc_buffer(len: u32) -> Result<u32, JsValue> {
+    if len == 0 {
+        return Err(error(ErrorCode::InvalidLength, "buffer length must be at least 1"));
+    }
+
+    let mut buffer = vec![0u8; len as usize].into_boxed_slice();
+    let ptr = buffer.as_mut_ptr() as u32;
+    BUFFERS.with(|buffers| buffers.borrow_mut().insert(ptr, buffer));
+    Ok(ptr)
+}
+
+// unknown or already freed pointers are ignored
+#[wasm_bindgen]
+pub fn free_buffer(ptr: u32) {
+    BUFFERS.with(|buffers| buffers.borrow_mut().remove(&ptr));
+}
+
+// Uint8Array over `len` bytes at `ptr`, no copy. see above for when it goes stale
+#[wasm_bindgen]
+pub fn buffer_view(ptr: u32, len: u32) -> Result<Uint8Array, JsValue> {
+    with_buffer(ptr, len, |_| ())?;
+    let memory: WebAssembly::Memory = wasm_bindgen::memory().unchecked_into();
+    Ok(Uint8Array::new_with_byte_offset_and_length(&memory.buffer(), ptr, len))
+}
+
+// invert_colors on a buffer from alloc_buffer
+#[wasm_bindgen]
+pub fn invert_in_place(ptr: u32, len: u32, width: u32, height: u32) -> Result<(), JsValue> {
+    check_size(len, width, height)?;
+    Ok(with_buffer(ptr, len, invert_pixels)?)
+}
+
+// the checks below return a plain ImageError so they run on the host too,
+// `?` in the exports turns it into a JsValue
+pub(crate) fn check_size(len: u32, width: u32, height: u32) -> Result<(), ImageError> {
+    if width as u64 * height as u64 * 4 != len as u64 {
+        return Err(ImageError::new(ErrorCode::InvalidLength, "len must be width * height * 4"));
+    }
+    Ok(())
+}
+
+// runs `f` on the first `len` bytes of a live buffer
+pub(crate) fn with_buffer<T>(ptr: u32, len: u32, f: impl FnOnce(&mut [u8]) -> T) -> Result<T, ImageError> {
+    BUFFERS.with(|buffers| {
+        let mut buffers = buffers.borrow_mut();
+        let buffer = buffers
+            .get_mut(&ptr)
+            .ok_or_else(|| ImageError::new(ErrorCode::OutOfBounds, "not a live buffer from alloc_buffer"))?;
+        if len as usize > buffer.len() {
+            return Err(ImageError::new(ErrorCode::OutOfBounds, "len is past the end of the buffer"));
+        }
+        Ok(f(&mut buffer[..len as usize]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_lifecycle() {
+        let ptr = alloc_buffer(16).unwrap();
+        // starts zeroed, and writes stick between calls
+        with_buffer(ptr, 16, |data| {
+            assert_eq!(data, &[0; 16]);
+            data[5] = 42;
+        })
+        .unwrap();
+        assert_eq!(with_buffer(ptr, 8, |data| data[5]).unwrap(), 42);
+
+        free_buffer(ptr);
+        let err = with_buffer(ptr, 16, |_| ()).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::OutOfBounds);
+        // freeing twice is fine
+        free_buffer(ptr);
+    }
+
+    #[test]
+    fn rejects_bad_lengths() {
+        let ptr = alloc_buffer(16).unwrap();
+        let err = with_buffer(ptr, 17, |_| ()).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::OutOfBounds);
+        free_buffer(ptr);
+
+        assert!(check_size(16, 2, 2).is_ok());
+        assert_eq!(check_size(16, 3, 2).unwrap_err().code(), ErrorCode::InvalidLength);
+        // width * height * 4 past u32 mustn't wrap around to a match
+        assert_eq!(check_size(0, 1 << 16, 1 << 14).unwrap_err().code(), ErrorCode::InvalidLength);
+    }
+}
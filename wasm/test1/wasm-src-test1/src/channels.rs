@@ -0,0 +1,53 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+// per-pixel channel remaps, mostly for debugging and BGR <-> RGB mixups
+//   0/1/2 = red/green/blue as grayscale
+//   3     = swap red and blue
+//   4/5/6 = zero out red/green/blue
+#[wasm_bindgen]
+pub fn channel_op(image_data: &ImageData, mode: u8) -> Result<ImageData, JsValue> {
+    if mode > 6 {
+        return Err(error(ErrorCode::InvalidMode, "unknown channel_op mode, expected 0-6"));
+    }
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let mut data = image_data.data().0;
+    channel_op_pixels(&mut data, mode);
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&data), width, height)
+}
+
+fn channel_op_pixels(data: &mut [u8], mode: u8) {
+    for px in data.chunks_exact_mut(4) {
+        match mode {
+            0..=2 => {
+                let v = px[mode as usize];
+                px[0] = v;
+                px[1] = v;
+                px[2] = v;
+            }
+            3 => px.swap(0, 2),
+            _ => px[(mode - 4) as usize] = 0,
+        }
+        // leave alpha alone
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swap_mode_round_trips() {
+        let original = vec![10, 20, 30, 40, 250, 0, 128, 255];
+        let mut data = original.clone();
+
+        channel_op_pixels(&mut data, 3);
+        assert_eq!(data, [30, 20, 10, 40, 128, 0, 250, 255]);
+        channel_op_pixels(&mut data, 3);
+        assert_eq!(data, original);
+    }
+}
@@ -0,0 +1,116 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+// green screen keying. colors are scaled up until the brightest channel is 255
+// and the distance is measured on the Cb/Cr plane of that, so a shadowed patch
+// of the screen (same hue, lower brightness) still keys out.
+// tolerance and softness are fractions of 255 in that plane:
+//   distance <= tolerance              -> fully transparent
+//   tolerance .. tolerance + softness  -> alpha ramps back up
+//   further away                       -> untouched
+// rgb is kept as is so the page can composite over any background
+#[wasm_bindgen]
+pub fn chroma_key(
+    image_data: &ImageData,
+    key_color: &[u8],
+    tolerance: f32,
+    softness: f32,
+    spill_suppression: bool,
+) -> Result<ImageData, JsValue> {
+    if key_color.len() < 3 {
+        return Err(error(ErrorCode::InvalidLength, "key_color needs r, g, b"));
+    }
+    if !(0.0..=1.0).contains(&tolerance) || !(0.0..=1.0).contains(&softness) {
+        return Err(error(ErrorCode::InvalidParameter, "tolerance and softness must be in 0..1"));
+    }
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let mut data = image_data.data().0;
+
+    key_pixels(&mut data, [key_color[0], key_color[1], key_color[2]], tolerance, softness, spill_suppression);
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&data), width, height)
+}
+
+fn key_pixels(data: &mut [u8], key_color: [u8; 3], tolerance: f32, softness: f32, spill_suppression: bool) {
+    let (key_cb, key_cr) = chroma(key_color[0], key_color[1], key_color[2]);
+    let inner = tolerance * 255.0;
+    let outer = inner + softness * 255.0;
+
+    for px in data.chunks_exact_mut(4) {
+        let (cb, cr) = chroma(px[0], px[1], px[2]);
+        let distance = (cb - key_cb).hypot(cr - key_cr);
+
+        // 0 = key color, 1 = keep
+        let keep = if distance <= inner {
+            0.0
+        } else if distance >= outer {
+            1.0
+        } else {
+            (distance - inner) / (outer - inner)
+        };
+
+        // edge pixels still carry a tint of the screen, pull them towards gray
+        // by however much of the key they contain
+        if spill_suppression && keep > 0.0 && keep < 1.0 {
+            let luma = 0.299 * px[0] as f32 + 0.587 * px[1] as f32 + 0.114 * px[2] as f32;
+            for v in px[..3].iter_mut() {
+                *v = (luma + (*v as f32 - luma) * keep).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+
+        px[3] = (px[3] as f32 * keep).round() as u8;
+    }
+}
+
+// BT.601 Cb/Cr without the +128 offset, only differences matter here.
+// raw Cb/Cr shrink with brightness, so the color is normalized first. below
+// MIN_BRIGHTNESS there's too little color left to tell hues apart, those are
+// scaled as if they were that bright and stay close to gray
+const MIN_BRIGHTNESS: f32 = 32.0;
+
+fn chroma(r: u8, g: u8, b: u8) -> (f32, f32) {
+    let scale = 255.0 / (r.max(g).max(b) as f32).max(MIN_BRIGHTNESS);
+    let (r, g, b) = (r as f32 * scale, g as f32 * scale, b as f32 * scale);
+    let cb = -0.168736 * r - 0.331264 * g + 0.5 * b;
+    let cr = 0.5 * r - 0.418688 * g - 0.081312 * b;
+    (cb, cr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_out_pure_green_only() {
+        let mut data = vec![
+            0, 255, 0, 255,
+            255, 0, 0, 255,
+            0, 0, 255, 255,
+            200, 200, 200, 255,
+            0, 255, 0, 255,
+        ];
+        key_pixels(&mut data, [0, 255, 0], 0.1, 0.1, false);
+
+        let alphas: Vec<u8> = data.chunks_exact(4).map(|px| px[3]).collect();
+        assert_eq!(alphas, [0, 255, 255, 255, 0]);
+        // rgb is left for the page to composite
+        assert_eq!(&data[..3], &[0, 255, 0]);
+    }
+
+    #[test]
+    fn keys_out_shadowed_green() {
+        let mut data = vec![
+            0, 100, 0, 255,
+            10, 120, 10, 255,
+            0, 0, 0, 255,
+            10, 10, 10, 255,
+        ];
+        key_pixels(&mut data, [0, 255, 0], 0.1, 0.1, false);
+
+        let alphas: Vec<u8> = data.chunks_exact(4).map(|px| px[3]).collect();
+        assert_eq!(alphas, [0, 0, 255, 255]);
+    }
+}
@@ -0,0 +1,21 @@
+// shared color math, the sRGB transfer functions are in wasm_src_common::linear
+
+// applies the same 256 entry table to r, g and b, alpha untouched
+pub(crate) fn apply_lut_rgb(data: &mut [u8], lut: &[u8; 256]) {
+    for px in data.chunks_exact_mut(4) {
+        px[0] = lut[px[0] as usize];
+        px[1] = lut[px[1] as usize];
+        px[2] = lut[px[2] as usize];
+    }
+}
+
+// per-channel counts for r, g and b
+pub(crate) fn channel_histograms(data: &[u8]) -> [[u32; 256]; 3] {
+    let mut hist = [[0u32; 256]; 3];
+    for px in data.chunks_exact(4) {
+        hist[0][px[0] as usize] += 1;
+        hist[1][px[1] as usize] += 1;
+        hist[2][px[2] as usize] += 1;
+    }
+    hist
+}
@@ -0,0 +1,364 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+#[wasm_bindgen]
+pub struct DiffResult {
+    image: ImageData,
+    changed_pixels: u32,
+    mean_error: [f64; 3],
+    bounding_box: Option<[u32; 4]>,
+}
+
+#[wasm_bindgen]
+impl DiffResult {
+    // per-pixel |a - b| (times amplify), opaque
+    #[wasm_bindgen(getter)]
+    pub fn image(&self) -> ImageData {
+        self.image.clone()
+    }
+
+    // pixels where some channel differs by more than the threshold
+    #[wasm_bindgen(getter)]
+    pub fn changed_pixels(&self) -> u32 {
+        self.changed_pixels
+    }
+
+    // mean absolute error for r, g, b (before amplification)
+    #[wasm_bindgen(getter)]
+    pub fn mean_error(&self) -> Vec<f64> {
+        self.mean_error.to_vec()
+    }
+
+    // [x, y, width, height] around the changed pixels, undefined if none changed
+    #[wasm_bindgen(getter)]
+    pub fn bounding_box(&self) -> Option<Vec<u32>> {
+        self.bounding_box.map(|b| b.to_vec())
+    }
+}
+
+// compares two same-sized images, e.g. the JS and WASM output of one filter or
+// two webcam frames. amplify scales the difference image so small errors show up
+#[wasm_bindgen]
+pub fn diff(image_a: &ImageData, image_b: &ImageData, amplify: f32, threshold: u8) -> Result<DiffResult, JsValue> {
+    if image_a.width() != image_b.width() || image_a.height() != image_b.height() {
+        return Err(error(ErrorCode::InvalidDimensions, "images must have the same dimensions"));
+    }
+    if !(amplify.is_finite() && amplify >= 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "amplify must be a non-negative number"));
+    }
+
+    let width = image_a.width() as usize;
+    let height = image_a.height() as usize;
+    let a = image_a.data();
+    let b = image_b.data();
+
+    let mut output = vec![0u8; a.len()];
+    let mut sums = [0u64; 3];
+    let mut changed = 0u32;
+    // min x, min y, max x, max y
+    let mut bounds: Option<[usize; 4]> = None;
+
+    for y in 0..height {
+        for x in 0..width {
+            let idx = (y * width + x) * 4;
+            let mut max_diff = 0u8;
+
+            for c in 0..3 {
+                let d = a[idx + c].abs_diff(b[idx + c]);
+                sums[c] += d as u64;
+                max_diff = max_diff.max(d);
+                output[idx + c] = (d as f32 * amplify).round().min(255.0) as u8;
+            }
+            output[idx + 3] = 255;
+
+            if max_diff > threshold {
+                changed += 1;
+                bounds = Some(match bounds {
+                    None => [x, y, x, y],
+                    Some([x0, y0, x1, y1]) => [x0.min(x), y0.min(y), x1.max(x), y1.max(y)],
+                });
+            }
+        }
+    }
+
+    let count = (width * height).max(1) as f64;
+    Ok(DiffResult {
+        image: ImageData::new_with_u8_clamped_array_and_sh(
+            wasm_bindgen::Clamped(&output),
+            width as u32,
+            height as u32,
+        )?,
+        changed_pixels: changed,
+        mean_error: sums.map(|s| s as f64 / count),
+        bounding_box: bounds.map(|[x0, y0, x1, y1]| {
+            [x0 as u32, y0 as u32, (x1 - x0 + 1) as u32, (y1 - y0 + 1) as u32]
+        }),
+    })
+}
+
+#[wasm_bindgen]
+pub struct ImageDiff {
+    mean_error: [f64; 3],
+    max_error: [u8; 3],
+    psnr: [f64; 3],
+    image: Option<ImageData>,
+}
+
+#[wasm_bindgen]
+impl ImageDiff {
+    // mean absolute error for r, g, b
+    #[wasm_bindgen(getter)]
+    pub fn mean_error(&self) -> Vec<f64> {
+        self.mean_error.to_vec()
+    }
+
+    // largest absolute error for r, g, b
+    #[wasm_bindgen(getter)]
+    pub fn max_error(&self) -> Vec<u8> {
+        self.max_error.to_vec()
+    }
+
+    // r, g, b in dB, Infinity when the channel is identical
+    #[wasm_bindgen(getter)]
+    pub fn psnr(&self) -> Vec<f64> {
+        self.psnr.to_vec()
+    }
+
+    // |a - b| stretched so the largest error is 255, undefined unless asked for
+    #[wasm_bindgen(getter)]
+    pub fn image(&self) -> Option<ImageData> {
+        self.image.clone()
+    }
+}
+
+// error metrics between two same-sized images in one pass, for checking that
+// two implementations of a filter agree (e.g. quantize_integer vs quantize):
+// max_error tells whether they're within some tolerance, mean_error and psnr
+// how far off they are overall. with_image also returns the difference image,
+// stretched so even off-by-one errors show up. `diff` is the variant with a
+// change threshold and bounding box
+#[wasm_bindgen]
+pub fn image_diff(a: &ImageData, b: &ImageData, with_image: bool) -> Result<ImageDiff, JsValue> {
+    if a.width() != b.width() || a.height() != b.height() {
+        return Err(error(ErrorCode::InvalidDimensions, "images must have the same dimensions"));
+    }
+
+    let data_a = a.data();
+    let data_b = b.data();
+
+    let mut sums = [0u64; 3];
+    let mut max_error = [0u8; 3];
+    for (pa, pb) in data_a.chunks_exact(4).zip(data_b.chunks_exact(4)) {
+        for c in 0..3 {
+            let d = pa[c].abs_diff(pb[c]);
+            sums[c] += d as u64;
+            max_error[c] = max_error[c].max(d);
+        }
+    }
+
+    let image = if with_image {
+        let scale = 255.0 / max_error.into_iter().max().unwrap_or(0).max(1) as f64;
+        let mut output = vec![0u8; data_a.len()];
+        for ((out, pa), pb) in output.chunks_exact_mut(4).zip(data_a.chunks_exact(4)).zip(data_b.chunks_exact(4)) {
+            for c in 0..3 {
+                out[c] = (pa[c].abs_diff(pb[c]) as f64 * scale).round() as u8;
+            }
+            out[3] = 255;
+        }
+        Some(ImageData::new_with_u8_clamped_array_and_sh(
+            wasm_bindgen::Clamped(&output),
+            a.width(),
+            a.height(),
+        )?)
+    } else {
+        None
+    };
+
+    let count = (data_a.len() / 4).max(1) as f64;
+    Ok(ImageDiff {
+        mean_error: sums.map(|s| s as f64 / count),
+        max_error,
+        psnr: psnr_channels(&data_a, &data_b),
+        image,
+    })
+}
+
+// 8x8 window from the original SSIM paper (compare_metrics' default),
+// stabilizers for 8-bit data
+const SSIM_WINDOW: usize = 8;
+const SSIM_C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const SSIM_C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+#[wasm_bindgen]
+pub struct MetricsResult {
+    psnr: [f64; 3],
+    ssim: f64,
+    ssim_map: ImageData,
+}
+
+#[wasm_bindgen]
+impl MetricsResult {
+    // r, g, b in dB, Infinity when the channel is identical
+    #[wasm_bindgen(getter)]
+    pub fn psnr(&self) -> Vec<f64> {
+        self.psnr.to_vec()
+    }
+
+    // mean SSIM of the grayscale images, 1.0 means identical
+    #[wasm_bindgen(getter)]
+    pub fn ssim(&self) -> f64 {
+        self.ssim
+    }
+
+    // same size as the inputs, each pixel shows the SSIM of the window around it
+    // (white = identical, black = no structural similarity)
+    #[wasm_bindgen(getter)]
+    pub fn ssim_map(&self) -> ImageData {
+        self.ssim_map.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn compare_metrics(a: &ImageData, b: &ImageData) -> Result<MetricsResult, JsValue> {
+    if a.width() != b.width() || a.height() != b.height() {
+        return Err(error(ErrorCode::InvalidDimensions, "images must have the same dimensions"));
+    }
+
+    let width = a.width() as usize;
+    let height = a.height() as usize;
+    let data_a = a.data();
+    let data_b = b.data();
+
+    let (windows, ssim) = ssim_windows(&data_a, &data_b, width, height, SSIM_WINDOW);
+
+    // windows are clamped inside the image, so border pixels reuse the nearest one
+    let (win_w, win_h) = (width.min(SSIM_WINDOW), height.min(SSIM_WINDOW));
+    let cols = width + 1 - win_w;
+    let rows = height + 1 - win_h;
+    let mut map = vec![0u8; width * height * 4];
+    for y in 0..height {
+        let wy = y.saturating_sub(win_h / 2).min(rows - 1);
+        for x in 0..width {
+            let wx = x.saturating_sub(win_w / 2).min(cols - 1);
+            let value = (windows[wy * cols + wx].clamp(0.0, 1.0) * 255.0).round() as u8;
+            let idx = (y * width + x) * 4;
+            map[idx] = value;
+            map[idx + 1] = value;
+            map[idx + 2] = value;
+            map[idx + 3] = 255;
+        }
+    }
+
+    Ok(MetricsResult {
+        psnr: psnr_channels(&data_a, &data_b),
+        ssim,
+        ssim_map: ImageData::new_with_u8_clamped_array_and_sh(
+            wasm_bindgen::Clamped(&map),
+            width as u32,
+            height as u32,
+        )?,
+    })
+}
+
+fn psnr_channels(a: &[u8], b: &[u8]) -> [f64; 3] {
+    let mut squared = [0u64; 3];
+    for (pa, pb) in a.chunks_exact(4).zip(b.chunks_exact(4)) {
+        for c in 0..3 {
+            let d = pa[c].abs_diff(pb[c]) as u64;
+            squared[c] += d * d;
+        }
+    }
+
+    let count = (a.len() / 4).max(1) as f64;
+    squared.map(|s| {
+        if s == 0 {
+            f64::INFINITY
+        } else {
+            10.0 * (255.0 * 255.0 / (s as f64 / count)).log10()
+        }
+    })
+}
+
+// mean SSIM (Wang et al. 2004) of the rec.601 luma over every window x window
+// block, sliding one pixel at a time. 1.0 means identical, lower is worse;
+// compare_metrics gives the same with the paper's 8x8 plus a map and PSNR.
+// bigger windows care more about larger structures and smooth over noise
+#[wasm_bindgen]
+pub fn ssim(a: &ImageData, b: &ImageData, window: usize) -> Result<f64, JsValue> {
+    if a.width() != b.width() || a.height() != b.height() {
+        return Err(error(ErrorCode::InvalidDimensions, "images must have the same dimensions"));
+    }
+    if window == 0 {
+        return Err(error(ErrorCode::InvalidParameter, "window must be at least 1"));
+    }
+
+    let (_, mean) = ssim_windows(&a.data(), &b.data(), a.width() as usize, a.height() as usize, window);
+    Ok(mean)
+}
+
+// SSIM of every window x window block (stride 1) plus their mean. images
+// smaller than the window use a single window covering that dimension
+fn ssim_windows(a: &[u8], b: &[u8], width: usize, height: usize, window: usize) -> (Vec<f64>, f64) {
+    if width == 0 || height == 0 {
+        return (vec![1.0], 1.0);
+    }
+
+    let gray = |data: &[u8]| -> Vec<u64> {
+        data.chunks_exact(4)
+            .map(|px| (0.299 * px[0] as f64 + 0.587 * px[1] as f64 + 0.114 * px[2] as f64).round() as u64)
+            .collect()
+    };
+    let ga = gray(a);
+    let gb = gray(b);
+
+    // integer summed-area tables so the window sums are exact
+    let stride = width + 1;
+    let mut sums = vec![[0u64; 5]; stride * (height + 1)];
+    for y in 0..height {
+        let mut row = [0u64; 5];
+        for x in 0..width {
+            let (va, vb) = (ga[y * width + x], gb[y * width + x]);
+            for (acc, v) in row.iter_mut().zip([va, vb, va * va, vb * vb, va * vb]) {
+                *acc += v;
+            }
+            let above = sums[y * stride + x + 1];
+            let cell = &mut sums[(y + 1) * stride + x + 1];
+            for k in 0..5 {
+                cell[k] = above[k] + row[k];
+            }
+        }
+    }
+
+    let (win_w, win_h) = (width.min(window), height.min(window));
+    let n = (win_w * win_h) as f64;
+    let cols = width + 1 - win_w;
+    let rows = height + 1 - win_h;
+    let mut windows = Vec::with_capacity(cols * rows);
+
+    for y in 0..rows {
+        for x in 0..cols {
+            let (x1, y1) = (x + win_w, y + win_h);
+            let mut s = [0f64; 5];
+            for (k, value) in s.iter_mut().enumerate() {
+                let total = sums[y1 * stride + x1][k] + sums[y * stride + x][k]
+                    - sums[y * stride + x1][k]
+                    - sums[y1 * stride + x][k];
+                *value = total as f64;
+            }
+
+            let mean_a = s[0] / n;
+            let mean_b = s[1] / n;
+            let var_a = s[2] / n - mean_a * mean_a;
+            let var_b = s[3] / n - mean_b * mean_b;
+            let cov = s[4] / n - mean_a * mean_b;
+
+            let num = (2.0 * mean_a * mean_b + SSIM_C1) * (2.0 * cov + SSIM_C2);
+            let den = (mean_a * mean_a + mean_b * mean_b + SSIM_C1) * (var_a + var_b + SSIM_C2);
+            windows.push(num / den);
+        }
+    }
+
+    let mean = windows.iter().sum::<f64>() / windows.len() as f64;
+    (windows, mean)
+}
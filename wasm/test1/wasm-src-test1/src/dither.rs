@@ -0,0 +1,71 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::color::apply_lut_rgb;
+
+// keeps `bits` bits per rgb channel (1-8), i.e. 2^bits evenly spaced levels
+// from 0 to 255. without dither it's a plain posterize, with it the rounding
+// error is spread floyd-steinberg style so gradients don't band. alpha is kept
+#[wasm_bindgen]
+pub fn quantize_bits(image_data: &ImageData, bits: u8, dither: bool) -> Result<ImageData, JsValue> {
+    if !(1..=8).contains(&bits) {
+        return Err(error(ErrorCode::InvalidParameter, "bits must be between 1 and 8"));
+    }
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let mut data = image_data.data().0;
+
+    let steps = ((1u32 << bits) - 1) as f32;
+    let nearest = |v: f32| ((v.clamp(0.0, 255.0) / 255.0 * steps).round() / steps * 255.0).round() as u8;
+
+    if dither {
+        floyd_steinberg(&mut data, width, height, nearest);
+    } else {
+        let mut lut = [0u8; 256];
+        for (i, v) in lut.iter_mut().enumerate() {
+            *v = nearest(i as f32);
+        }
+        apply_lut_rgb(&mut data, &lut);
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        width as u32,
+        height as u32,
+    )
+}
+
+// error diffusion on the rgb channels, `nearest` snaps a (possibly out of
+// range) value to an output level. the carried error only ever needs the
+// current and next row, so two rows of floats instead of a whole image
+pub(crate) fn floyd_steinberg(data: &mut [u8], width: usize, height: usize, nearest: impl Fn(f32) -> u8) {
+    let mut current = vec![0f32; width * 3];
+    let mut next = vec![0f32; width * 3];
+
+    for y in 0..height {
+        for x in 0..width {
+            for c in 0..3 {
+                let idx = (y * width + x) * 4 + c;
+                let wanted = data[idx] as f32 + current[x * 3 + c];
+                let out = nearest(wanted);
+                data[idx] = out;
+
+                //        *   7
+                //    3   5   1     (/16)
+                let err = wanted - out as f32;
+                if x + 1 < width {
+                    current[(x + 1) * 3 + c] += err * 7.0 / 16.0;
+                    next[(x + 1) * 3 + c] += err * 1.0 / 16.0;
+                }
+                if x > 0 {
+                    next[(x - 1) * 3 + c] += err * 3.0 / 16.0;
+                }
+                next[x * 3 + c] += err * 5.0 / 16.0;
+            }
+        }
+        std::mem::swap(&mut current, &mut next);
+        next.fill(0.0);
+    }
+}
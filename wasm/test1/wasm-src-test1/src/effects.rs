@@ -0,0 +1,247 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::rng::Rng;
+
+// darkens with distance from (center_x, center_y), both 0..1 across the image.
+// distances are normalized so the half diagonal is 1:
+//   d < radius                  -> untouched
+//   radius .. radius + feather  -> smoothstep falloff
+//   beyond                      -> rgb *= 1 - strength
+// strength is -1..1, 0 is a no-op and negatives brighten instead
+// (this replaced the first version's rgb *= 1 - strength * (d / max)^2 over the
+// whole image, the smoothstep band is what callers get now)
+#[wasm_bindgen]
+pub fn vignette(
+    image_data: &ImageData,
+    strength: f32,
+    radius: f32,
+    feather: f32,
+    center_x: f32,
+    center_y: f32,
+) -> Result<ImageData, JsValue> {
+    if !(-1.0..=1.0).contains(&strength) {
+        return Err(error(ErrorCode::InvalidParameter, "vignette strength must be between -1 and 1"));
+    }
+    if !(radius.is_finite() && radius >= 0.0 && feather.is_finite() && feather >= 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "radius and feather must be non-negative numbers"));
+    }
+    if !(center_x.is_finite() && center_y.is_finite()) {
+        return Err(error(ErrorCode::InvalidParameter, "center must be finite"));
+    }
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let mut data = image_data.data().0;
+
+    if strength != 0.0 {
+        vignette_pixels(
+            &mut data,
+            width,
+            height,
+            strength as f64,
+            radius as f64,
+            feather as f64,
+            (center_x as f64, center_y as f64),
+        );
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        width as u32,
+        height as u32,
+    )
+}
+
+// runs per video frame, so the squared distance is stepped along each row and
+// the sqrt is only taken inside the feather band
+fn vignette_pixels(
+    data: &mut [u8],
+    width: usize,
+    height: usize,
+    strength: f64,
+    radius: f64,
+    feather: f64,
+    center: (f64, f64),
+) {
+    let half_diagonal = ((width * width + height * height) as f64).sqrt() / 2.0;
+    if half_diagonal == 0.0 {
+        return;
+    }
+    // everything below in pixel units
+    let inner = radius * half_diagonal;
+    let outer = (radius + feather) * half_diagonal;
+    let (inner_sq, outer_sq) = (inner * inner, outer * outer);
+    let cx = center.0 * width as f64 - 0.5;
+    let cy = center.1 * height as f64 - 0.5;
+
+    for y in 0..height {
+        let dy = y as f64 - cy;
+        let dx0 = -cx;
+        let mut dist_sq = dx0 * dx0 + dy * dy;
+        // (dx + 1)^2 - dx^2 = 2dx + 1, which itself grows by 2 per pixel
+        let mut step = 2.0 * dx0 + 1.0;
+
+        for x in 0..width {
+            let falloff = if dist_sq <= inner_sq {
+                0.0
+            } else if dist_sq >= outer_sq {
+                1.0
+            } else {
+                let t = (dist_sq.sqrt() - inner) / (outer - inner);
+                t * t * (3.0 - 2.0 * t)
+            };
+            dist_sq += step;
+            step += 2.0;
+
+            if falloff == 0.0 {
+                continue;
+            }
+            let factor = 1.0 - strength * falloff;
+            let idx = (y * width + x) * 4;
+            for value in data[idx..idx + 3].iter_mut() {
+                *value = (*value as f64 * factor).round().clamp(0.0, 255.0) as u8;
+            }
+            // leave alpha alone
+        }
+    }
+}
+
+// gaussian noise, alpha untouched. monochrome adds the same value to r, g and b
+// (film grain), otherwise each channel gets its own (color noise).
+// `amount` is the stddev as a fraction of the 0..255 range (0.1 -> stddev 25.5)
+#[wasm_bindgen]
+pub fn add_noise(image_data: &ImageData, amount: f32, monochrome: bool, seed: u64) -> Result<ImageData, JsValue> {
+    if !(amount.is_finite() && amount >= 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "noise amount must be a non-negative number"));
+    }
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let mut data = image_data.data().0;
+
+    let stddev = amount as f64 * 255.0;
+    let mut rng = Rng::new(seed);
+
+    for px in data.chunks_exact_mut(4) {
+        let grain = if monochrome { rng.next_gaussian() * stddev } else { 0.0 };
+        for value in px.iter_mut().take(3) {
+            let offset = if monochrome { grain } else { rng.next_gaussian() * stddev };
+            *value = (*value as f64 + offset).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&data), width, height)
+}
+
+// maps luminance onto a two color gradient: black -> shadow, white -> highlight
+#[wasm_bindgen]
+pub fn duotone(image_data: &ImageData, shadow: &[u8], highlight: &[u8]) -> Result<ImageData, JsValue> {
+    if shadow.len() != 3 || highlight.len() != 3 {
+        return Err(error(ErrorCode::InvalidLength, "shadow and highlight must be rgb triples"));
+    }
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let mut data = image_data.data().0;
+
+    let stops = [shadow[0], shadow[1], shadow[2], highlight[0], highlight[1], highlight[2]];
+    map_luma(&mut data, &gradient_lut(&stops, &[0.0, 1.0]));
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&data), width, height)
+}
+
+// duotone with any number of stops: `stops` is flat rgb, `positions` says
+// where each stop sits on the 0..1 luminance axis (sorted). lumas before the
+// first or after the last stop take that stop's color
+#[wasm_bindgen]
+pub fn gradient_map(image_data: &ImageData, stops: &[u8], positions: &[f64]) -> Result<ImageData, JsValue> {
+    if positions.is_empty() || stops.len() != positions.len() * 3 {
+        return Err(error(ErrorCode::InvalidLength, "stops must hold one rgb triple per position"));
+    }
+    if positions.iter().any(|p| !(0.0..=1.0).contains(p)) || positions.windows(2).any(|w| w[0] > w[1]) {
+        return Err(error(ErrorCode::InvalidParameter, "positions must be sorted and within 0..1"));
+    }
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let mut data = image_data.data().0;
+
+    map_luma(&mut data, &gradient_lut(stops, positions));
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&data), width, height)
+}
+
+// only 256 possible lumas, so the gradient is built once as a table
+fn gradient_lut(stops: &[u8], positions: &[f64]) -> [[u8; 3]; 256] {
+    let mut lut = [[0u8; 3]; 256];
+    for (l, color) in lut.iter_mut().enumerate() {
+        let t = l as f64 / 255.0;
+        // first stop at or after t, clamped to the ends
+        let upper = positions.iter().position(|&p| p >= t).unwrap_or(positions.len() - 1);
+        let lower = if positions[upper] <= t { upper } else { upper.saturating_sub(1) };
+
+        let (a, b) = (&stops[lower * 3..lower * 3 + 3], &stops[upper * 3..upper * 3 + 3]);
+        let span = positions[upper] - positions[lower];
+        let f = if span > 0.0 { ((t - positions[lower]) / span).clamp(0.0, 1.0) } else { 0.0 };
+        *color = [0, 1, 2].map(|c| (a[c] as f64 + (b[c] as f64 - a[c] as f64) * f).round() as u8);
+    }
+    lut
+}
+
+fn map_luma(data: &mut [u8], lut: &[[u8; 3]; 256]) {
+    for px in data.chunks_exact_mut(4) {
+        // rec.601 luma
+        let luma = (0.299 * px[0] as f64 + 0.587 * px[1] as f64 + 0.114 * px[2] as f64).round() as usize;
+        px[..3].copy_from_slice(&lut[luma.min(255)]);
+        // leave alpha alone
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flat(width: usize, height: usize, value: u8) -> Vec<u8> {
+        (0..width * height).flat_map(|_| [value, value, value, 255]).collect()
+    }
+
+    #[test]
+    fn vignette_darkens_corners_only() {
+        let (width, height) = (9, 7);
+        let mut data = flat(width, height, 200);
+        vignette_pixels(&mut data, width, height, 0.8, 0.5, 0.5, (0.5, 0.5));
+
+        let center = (3 * width + 4) * 4;
+        assert_eq!(&data[center..center + 4], &[200, 200, 200, 255]);
+        for (x, y) in [(0, 0), (width - 1, 0), (0, height - 1), (width - 1, height - 1)] {
+            let idx = (y * width + x) * 4;
+            assert!(data[idx] < 100, "corner ({}, {}) is {}", x, y, data[idx]);
+            assert_eq!(data[idx + 3], 255);
+        }
+    }
+
+    #[test]
+    fn vignette_strength_zero_is_a_no_op() {
+        let (width, height) = (9, 7);
+        let original: Vec<u8> = (0..width * height * 4).map(|i| (i * 7) as u8).collect();
+        let mut data = original.clone();
+        vignette_pixels(&mut data, width, height, 0.0, 0.2, 0.3, (0.3, 0.7));
+        assert_eq!(data, original);
+    }
+
+    #[test]
+    fn duotone_maps_black_to_shadow_and_white_to_highlight() {
+        let shadow = [20, 40, 120];
+        let highlight = [250, 200, 10];
+        let stops = [shadow[0], shadow[1], shadow[2], highlight[0], highlight[1], highlight[2]];
+
+        let mut data = vec![0, 0, 0, 255, 255, 255, 255, 128];
+        map_luma(&mut data, &gradient_lut(&stops, &[0.0, 1.0]));
+
+        assert_eq!(&data[0..4], &[20, 40, 120, 255]);
+        // alpha is kept
+        assert_eq!(&data[4..8], &[250, 200, 10, 128]);
+    }
+}
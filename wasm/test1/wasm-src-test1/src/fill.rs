@@ -0,0 +1,130 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+// paint bucket: recolors every pixel 4-connected to (x, y) whose color is within
+// `tolerance` of the seed pixel. distance is the "redmean" weighted rgb distance,
+// a cheap perceptual approximation that runs 0..~765 (0 = exact match only).
+// contiguous = false skips the connectivity and replaces every similar pixel.
+// fill_rgba is r, g, b with an optional alpha, opaque if left out
+#[wasm_bindgen]
+pub fn flood_fill(
+    image_data: &ImageData,
+    x: u32,
+    y: u32,
+    fill_rgba: &[u8],
+    tolerance: f32,
+    contiguous: bool,
+) -> Result<ImageData, JsValue> {
+    let width = image_data.width();
+    let height = image_data.height();
+
+    if x >= width || y >= height {
+        return Err(error(ErrorCode::OutOfBounds, "seed pixel is outside the image"));
+    }
+    let fill = match *fill_rgba {
+        [r, g, b] => [r, g, b, 255],
+        [r, g, b, a] => [r, g, b, a],
+        _ => return Err(error(ErrorCode::InvalidLength, "fill_rgba must have 3 or 4 values")),
+    };
+    if !(tolerance.is_finite() && tolerance >= 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "tolerance must be a non-negative number"));
+    }
+
+    let mut data = image_data.data().0;
+    let seed = ((y * width + x) * 4) as usize;
+
+    // nothing would change, and it's the classic way to loop forever
+    if data[seed..seed + 4] == fill {
+        return ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&data), width, height);
+    }
+
+    let mask = flood_mask(
+        &data,
+        width as usize,
+        height as usize,
+        x as usize,
+        y as usize,
+        tolerance as f64,
+        contiguous,
+    );
+
+    for (px, selected) in data.chunks_exact_mut(4).zip(mask) {
+        if selected {
+            px.copy_from_slice(&fill);
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&data), width, height)
+}
+
+// pixels selected by the fill, compared against the seed's original color
+fn flood_mask(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+    tolerance: f64,
+    contiguous: bool,
+) -> Vec<bool> {
+    let seed = (y * width + x) * 4;
+    let seed_rgb = [data[seed], data[seed + 1], data[seed + 2]];
+    let tolerance_sq = tolerance * tolerance;
+    let matches = |i: usize| {
+        let idx = i * 4;
+        redmean_distance_sq(&data[idx..idx + 3], &seed_rgb) <= tolerance_sq
+    };
+
+    if !contiguous {
+        return (0..width * height).map(matches).collect();
+    }
+
+    // scanline fill: each popped seed grows into a whole horizontal span, then
+    // pushes one seed per matching run in the rows above and below. explicit
+    // stack, recursion would blow the wasm stack on big regions
+    let mut mask = vec![false; width * height];
+    let mut stack = vec![(x, y)];
+
+    while let Some((sx, sy)) = stack.pop() {
+        let row = sy * width;
+        if mask[row + sx] {
+            continue;
+        }
+
+        let mut left = sx;
+        while left > 0 && !mask[row + left - 1] && matches(row + left - 1) {
+            left -= 1;
+        }
+        let mut right = sx;
+        while right + 1 < width && !mask[row + right + 1] && matches(row + right + 1) {
+            right += 1;
+        }
+        mask[row + left..=row + right].fill(true);
+
+        let neighbours = [sy.checked_sub(1), Some(sy + 1).filter(|&ny| ny < height)];
+        for ny in neighbours.into_iter().flatten() {
+            let nrow = ny * width;
+            let mut in_run = false;
+            for nx in left..=right {
+                let open = !mask[nrow + nx] && matches(nrow + nx);
+                if open && !in_run {
+                    stack.push((nx, ny));
+                }
+                in_run = open;
+            }
+        }
+    }
+
+    mask
+}
+
+// weights the channels by how sensitive the eye is to them, with the red/blue
+// balance shifting with how red the pair is
+fn redmean_distance_sq(a: &[u8], b: &[u8; 3]) -> f64 {
+    let rmean = (a[0] as f64 + b[0] as f64) / 2.0;
+    let dr = a[0] as f64 - b[0] as f64;
+    let dg = a[1] as f64 - b[1] as f64;
+    let db = a[2] as f64 - b[2] as f64;
+    (2.0 + rmean / 256.0) * dr * dr + 4.0 * dg * dg + (2.0 + (255.0 - rmean) / 256.0) * db * db
+}
@@ -0,0 +1,577 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode, ImageError};
+use wasm_src_common::linear::{decode_lut, encode};
+
+// pure index remaps, no interpolation - output is exact
+
+#[wasm_bindgen]
+pub fn flip_horizontal(image_data: &ImageData) -> Result<ImageData, JsValue> {
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let out = flip_horizontal_pixels(&image_data.data(), width, height);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&out),
+        width as u32,
+        height as u32,
+    )
+}
+
+#[wasm_bindgen]
+pub fn flip_vertical(image_data: &ImageData) -> Result<ImageData, JsValue> {
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let out = flip_vertical_pixels(&image_data.data(), width, height);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&out),
+        width as u32,
+        height as u32,
+    )
+}
+
+// width and height swap, so the output is (height x width)
+#[wasm_bindgen]
+pub fn rotate_90(image_data: &ImageData, clockwise: bool) -> Result<ImageData, JsValue> {
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let (out, out_width, out_height) = rotate_90_pixels(&image_data.data(), width, height, clockwise);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&out),
+        out_width as u32,
+        out_height as u32,
+    )
+}
+
+fn flip_horizontal_pixels(data: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut out = vec![0u8; data.len()];
+
+    for y in 0..height {
+        for x in 0..width {
+            let src = (y * width + x) * 4;
+            let dst = (y * width + (width - 1 - x)) * 4;
+            out[dst..dst + 4].copy_from_slice(&data[src..src + 4]);
+        }
+    }
+
+    out
+}
+
+fn flip_vertical_pixels(data: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut out = vec![0u8; data.len()];
+    let stride = width * 4;
+
+    // whole rows move, so copy a row at a time
+    for y in 0..height {
+        let src = y * stride;
+        let dst = (height - 1 - y) * stride;
+        out[dst..dst + stride].copy_from_slice(&data[src..src + stride]);
+    }
+
+    out
+}
+
+// also hands back the rotated (width, height), which are the input's swapped
+fn rotate_90_pixels(data: &[u8], width: usize, height: usize, clockwise: bool) -> (Vec<u8>, usize, usize) {
+    let mut out = vec![0u8; data.len()];
+    let (out_width, out_height) = (height, width);
+
+    for y in 0..height {
+        for x in 0..width {
+            let (nx, ny) = if clockwise {
+                (height - 1 - y, x)
+            } else {
+                (y, width - 1 - x)
+            };
+            let src = (y * width + x) * 4;
+            let dst = (ny * out_width + nx) * 4;
+            out[dst..dst + 4].copy_from_slice(&data[src..src + 4]);
+        }
+    }
+
+    (out, out_width, out_height)
+}
+
+// extracts the (x, y, w, h) rectangle, which has to fit inside the source
+#[wasm_bindgen]
+pub fn crop(image_data: &ImageData, x: u32, y: u32, w: u32, h: u32) -> Result<ImageData, JsValue> {
+    let width = image_data.width();
+    let height = image_data.height();
+    check_crop(width, height, x, y, w, h)?;
+
+    let out = crop_pixels(
+        &image_data.data(),
+        width as usize,
+        x as usize,
+        y as usize,
+        w as usize,
+        h as usize,
+    );
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&out), w, h)
+}
+
+// mode 0 = nearest-neighbor, mode 1 = bilinear (interpolates all four channels)
+#[wasm_bindgen]
+pub fn resize(
+    image_data: &ImageData,
+    new_width: u32,
+    new_height: u32,
+    mode: u8,
+) -> Result<ImageData, JsValue> {
+    resize_opts(image_data, new_width, new_height, mode, false)
+}
+
+// `resize` where bilinear can interpolate in linear light, decoding sRGB first
+// and encoding the result. mixing in gamma encoded values darkens fine detail
+// and color edges when shrinking (a red/green checker turns olive instead of
+// yellow). nearest-neighbor never mixes, so `linear` changes nothing there
+#[wasm_bindgen]
+pub fn resize_opts(
+    image_data: &ImageData,
+    new_width: u32,
+    new_height: u32,
+    mode: u8,
+    linear: bool,
+) -> Result<ImageData, JsValue> {
+    if new_width == 0 || new_height == 0 {
+        return Err(error(ErrorCode::InvalidDimensions, "resize dimensions must be non-zero"));
+    }
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let data = image_data.data();
+    let (new_w, new_h) = (new_width as usize, new_height as usize);
+
+    let out = match mode {
+        0 => resize_nearest(&data, width, height, new_w, new_h),
+        1 if linear => resize_bilinear_linear(&data, width, height, new_w, new_h),
+        1 => resize_bilinear(&data, width, height, new_w, new_h),
+        _ => return Err(error(ErrorCode::InvalidMode, "unknown resize mode, expected 0 or 1")),
+    };
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&out),
+        new_width,
+        new_height,
+    )
+}
+
+// area averaging for big reductions: every factor x factor block becomes one
+// pixel, so nothing is skipped the way nearest/bilinear skip pixels when
+// shrinking a lot. blocks on the right and bottom edge are whatever is left
+// over and get averaged over their actual size, the output is
+// ceil(w / factor) x ceil(h / factor). color is weighted by alpha so
+// transparent pixels don't tint the edges of sprites
+#[wasm_bindgen]
+pub fn downsample_area(image_data: &ImageData, factor: usize) -> Result<ImageData, JsValue> {
+    if factor == 0 {
+        return Err(error(ErrorCode::InvalidParameter, "factor must be at least 1"));
+    }
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let (new_w, new_h) = (width.div_ceil(factor), height.div_ceil(factor));
+    let out = downsample_area_pixels(&image_data.data(), width, height, factor);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&out),
+        new_w as u32,
+        new_h as u32,
+    )
+}
+
+// twirl around (center_x, center_y), all in pixels. inside `radius` each
+// output pixel reads the source rotated by strength * (1 - dist / radius)
+// radians, so the twist is strongest in the middle and fades to nothing at the
+// edge of the circle. outside it the image passes through
+#[wasm_bindgen]
+pub fn swirl(
+    image_data: &ImageData,
+    center_x: f32,
+    center_y: f32,
+    radius: f32,
+    strength: f32,
+) -> Result<ImageData, JsValue> {
+    if !(radius.is_finite() && radius > 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "swirl radius must be a positive number"));
+    }
+    if !(center_x.is_finite() && center_y.is_finite() && strength.is_finite()) {
+        return Err(error(ErrorCode::InvalidParameter, "swirl center and strength must be finite"));
+    }
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let data = image_data.data();
+    let (cx, cy) = (center_x as f64, center_y as f64);
+    let (radius, strength) = (radius as f64, strength as f64);
+
+    let mut out = data.to_vec();
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f64 - cx;
+            let dy = y as f64 - cy;
+            let dist = (dx * dx + dy * dy).sqrt();
+            if dist >= radius {
+                continue;
+            }
+
+            // inverse mapping: rotate the output position to find its source
+            let (sin, cos) = (strength * (1.0 - dist / radius)).sin_cos();
+            let sx = cx + dx * cos - dy * sin;
+            let sy = cy + dx * sin + dy * cos;
+            let px = sample_bilinear(&data, width, height, sx, sy);
+
+            let dst = (y * width + x) * 4;
+            for c in 0..4 {
+                out[dst + c] = px[c].round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&out),
+        width as u32,
+        height as u32,
+    )
+}
+
+// radial lens model around the image center: a source point at normalized
+// radius r (0 at the center, 1 at the corners) lands at r * (1 + k1*r^2 + k2*r^4).
+// positive coefficients pincushion, negative barrel. useful values are small,
+// roughly -0.5..0.5 for k1 and -0.2..0.2 for k2; much past that the curve folds
+// back on itself and the fold comes out as empty pixels. pixels that come from
+// outside the source are transparent, or opaque black with transparent = false
+#[wasm_bindgen]
+pub fn lens_distort(image_data: &ImageData, k1: f32, k2: f32, transparent: bool) -> Result<ImageData, JsValue> {
+    if !(k1.is_finite() && k2.is_finite()) {
+        return Err(error(ErrorCode::InvalidParameter, "lens coefficients must be finite"));
+    }
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let data = image_data.data();
+    let (k1, k2) = (k1 as f64, k2 as f64);
+    let cx = (width as f64 - 1.0) / 2.0;
+    let cy = (height as f64 - 1.0) / 2.0;
+    let norm = (cx * cx + cy * cy).sqrt().max(1.0);
+    let empty = if transparent { [0, 0, 0, 0] } else { [0, 0, 0, 255] };
+
+    let mut out = vec![0u8; data.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let dx = (x as f64 - cx) / norm;
+            let dy = (y as f64 - cy) / norm;
+            let r_out = (dx * dx + dy * dy).sqrt();
+            let dst = (y * width + x) * 4;
+
+            let Some(r_in) = undistort_radius(r_out, k1, k2) else {
+                out[dst..dst + 4].copy_from_slice(&empty);
+                continue;
+            };
+            let scale = if r_out > 0.0 { r_in / r_out } else { 1.0 };
+            let sx = cx + dx * scale * norm;
+            let sy = cy + dy * scale * norm;
+
+            // half a pixel of slack so k = 0 keeps the border pixels
+            if sx < -0.5 || sy < -0.5 || sx > width as f64 - 0.5 || sy > height as f64 - 0.5 {
+                out[dst..dst + 4].copy_from_slice(&empty);
+                continue;
+            }
+            let px = sample_bilinear(&data, width, height, sx, sy);
+            for c in 0..4 {
+                out[dst + c] = px[c].round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&out),
+        width as u32,
+        height as u32,
+    )
+}
+
+fn check_crop(width: u32, height: u32, x: u32, y: u32, w: u32, h: u32) -> Result<(), ImageError> {
+    if w == 0 || h == 0 {
+        return Err(ImageError::new(ErrorCode::InvalidDimensions, "crop size must be non-zero"));
+    }
+    // u64 so a huge x + w can't wrap around and sneak past the check
+    if x as u64 + w as u64 > width as u64 || y as u64 + h as u64 > height as u64 {
+        return Err(ImageError::new(ErrorCode::OutOfBounds, "crop rectangle is outside the image"));
+    }
+    Ok(())
+}
+
+fn crop_pixels(data: &[u8], width: usize, x: usize, y: usize, w: usize, h: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(w * h * 4);
+    let stride = width * 4;
+
+    for row in y..y + h {
+        let start = row * stride + x * 4;
+        out.extend_from_slice(&data[start..start + w * 4]);
+    }
+
+    out
+}
+
+fn resize_nearest(data: &[u8], width: usize, height: usize, new_w: usize, new_h: usize) -> Vec<u8> {
+    let mut out = vec![0u8; new_w * new_h * 4];
+    let scale_x = width as f64 / new_w as f64;
+    let scale_y = height as f64 / new_h as f64;
+
+    for y in 0..new_h {
+        // sample at the pixel center, not the corner
+        let sy = (((y as f64 + 0.5) * scale_y) as usize).min(height - 1);
+        for x in 0..new_w {
+            let sx = (((x as f64 + 0.5) * scale_x) as usize).min(width - 1);
+            let src = (sy * width + sx) * 4;
+            let dst = (y * new_w + x) * 4;
+            out[dst..dst + 4].copy_from_slice(&data[src..src + 4]);
+        }
+    }
+
+    out
+}
+
+fn resize_bilinear(data: &[u8], width: usize, height: usize, new_w: usize, new_h: usize) -> Vec<u8> {
+    let mut out = vec![0u8; new_w * new_h * 4];
+    let scale_x = width as f64 / new_w as f64;
+    let scale_y = height as f64 / new_h as f64;
+
+    for y in 0..new_h {
+        // pixel centers line up, so same-size resize samples exact pixels
+        let sy = (y as f64 + 0.5) * scale_y - 0.5;
+        for x in 0..new_w {
+            let sx = (x as f64 + 0.5) * scale_x - 0.5;
+            let px = sample_bilinear(data, width, height, sx, sy);
+            let dst = (y * new_w + x) * 4;
+            for c in 0..4 {
+                out[dst + c] = px[c].round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    out
+}
+
+fn downsample_area_pixels(data: &[u8], width: usize, height: usize, factor: usize) -> Vec<u8> {
+    let (new_w, new_h) = (width.div_ceil(factor), height.div_ceil(factor));
+    let mut out = vec![0u8; new_w * new_h * 4];
+
+    for by in 0..new_h {
+        let rows = by * factor..((by + 1) * factor).min(height);
+        for bx in 0..new_w {
+            let cols = bx * factor..((bx + 1) * factor).min(width);
+            // rgb sums are weighted by alpha, alpha itself is a plain mean
+            let mut sums = [0u64; 4];
+            for y in rows.clone() {
+                for x in cols.clone() {
+                    let i = (y * width + x) * 4;
+                    let a = data[i + 3] as u64;
+                    for c in 0..3 {
+                        sums[c] += data[i + c] as u64 * a;
+                    }
+                    sums[3] += a;
+                }
+            }
+
+            let count = (rows.len() * cols.len()) as u64;
+            let dst = (by * new_w + bx) * 4;
+            // a fully transparent block has all rgb sums at 0 and comes out black
+            let weight = sums[3].max(1);
+            for c in 0..3 {
+                out[dst + c] = ((sums[c] + weight / 2) / weight) as u8;
+            }
+            out[dst + 3] = ((sums[3] + count / 2) / count) as u8;
+        }
+    }
+
+    out
+}
+
+// resize_bilinear on linear light rgb, alpha is interpolated as is
+fn resize_bilinear_linear(data: &[u8], width: usize, height: usize, new_w: usize, new_h: usize) -> Vec<u8> {
+    let lut = decode_lut();
+    let linear: Vec<f64> = data
+        .chunks_exact(4)
+        .flat_map(|px| [lut[px[0] as usize], lut[px[1] as usize], lut[px[2] as usize], px[3] as f64])
+        .collect();
+
+    let mut out = vec![0u8; new_w * new_h * 4];
+    let scale_x = width as f64 / new_w as f64;
+    let scale_y = height as f64 / new_h as f64;
+
+    for y in 0..new_h {
+        let sy = (y as f64 + 0.5) * scale_y - 0.5;
+        for x in 0..new_w {
+            let sx = (x as f64 + 0.5) * scale_x - 0.5;
+            let px = sample_bilinear(&linear, width, height, sx, sy);
+            let dst = (y * new_w + x) * 4;
+            for c in 0..3 {
+                out[dst + c] = encode(px[c]);
+            }
+            out[dst + 3] = px[3].round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    out
+}
+
+// reads a pixel at fractional coordinates, out of range coordinates clamp to the edge.
+// generic so it also runs on decoded float buffers
+fn sample_bilinear<T: Copy + Into<f64>>(data: &[T], width: usize, height: usize, x: f64, y: f64) -> [f64; 4] {
+    let x = x.clamp(0.0, (width - 1) as f64);
+    let y = y.clamp(0.0, (height - 1) as f64);
+    let x0 = x.floor() as usize;
+    let y0 = y.floor() as usize;
+    let x1 = (x0 + 1).min(width - 1);
+    let y1 = (y0 + 1).min(height - 1);
+    let fx = x - x0 as f64;
+    let fy = y - y0 as f64;
+
+    let mut px = [0f64; 4];
+    for (c, value) in px.iter_mut().enumerate() {
+        let p00 = data[(y0 * width + x0) * 4 + c].into();
+        let p10 = data[(y0 * width + x1) * 4 + c].into();
+        let p01 = data[(y1 * width + x0) * 4 + c].into();
+        let p11 = data[(y1 * width + x1) * 4 + c].into();
+        let top = p00 + (p10 - p00) * fx;
+        let bottom = p01 + (p11 - p01) * fx;
+        *value = top + (bottom - top) * fy;
+    }
+    px
+}
+
+// solves r * (1 + k1*r^2 + k2*r^4) = r_out for r with newton's method, None
+// where the curve isn't increasing (the model folded over) or it doesn't settle
+fn undistort_radius(r_out: f64, k1: f64, k2: f64) -> Option<f64> {
+    let mut r = r_out;
+    for _ in 0..20 {
+        let r2 = r * r;
+        let f = r * (1.0 + k1 * r2 + k2 * r2 * r2) - r_out;
+        let slope = 1.0 + 3.0 * k1 * r2 + 5.0 * k2 * r2 * r2;
+        if slope <= 0.0 {
+            return None;
+        }
+        let step = f / slope;
+        r -= step;
+        if step.abs() < 1e-9 {
+            return (r >= 0.0).then_some(r);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every byte distinct, so any misplaced pixel shows up
+    fn numbered(width: usize, height: usize) -> Vec<u8> {
+        (0..width * height * 4).map(|i| i as u8).collect()
+    }
+
+    fn pixel(data: &[u8], width: usize, x: usize, y: usize) -> &[u8] {
+        let idx = (y * width + x) * 4;
+        &data[idx..idx + 4]
+    }
+
+    #[test]
+    fn flips_round_trip() {
+        let (width, height) = (5, 3);
+        let data = numbered(width, height);
+
+        let flipped = flip_horizontal_pixels(&data, width, height);
+        assert_eq!(pixel(&flipped, width, 0, 1), pixel(&data, width, 4, 1));
+        assert_eq!(flip_horizontal_pixels(&flipped, width, height), data);
+
+        let flipped = flip_vertical_pixels(&data, width, height);
+        assert_eq!(pixel(&flipped, width, 1, 0), pixel(&data, width, 1, 2));
+        assert_eq!(flip_vertical_pixels(&flipped, width, height), data);
+    }
+
+    #[test]
+    fn four_rotations_are_identity() {
+        let (width, height) = (5, 3);
+        let data = numbered(width, height);
+
+        for clockwise in [true, false] {
+            // dimensions swap on every turn
+            let (mut out, mut w, mut h) = (data.clone(), width, height);
+            for _ in 0..4 {
+                (out, w, h) = rotate_90_pixels(&out, w, h, clockwise);
+            }
+            assert_eq!((out, w, h), (data.clone(), width, height));
+        }
+
+        // clockwise: the top-left corner ends up top-right of the 3x5 result
+        let (cw, _, _) = rotate_90_pixels(&data, width, height, true);
+        assert_eq!(pixel(&cw, height, height - 1, 0), pixel(&data, width, 0, 0));
+        // and one turn each way cancels out
+        assert_eq!(rotate_90_pixels(&cw, height, width, false).0, data);
+    }
+
+    #[test]
+    fn same_size_bilinear_is_identity() {
+        let (width, height) = (5, 3);
+        let data: Vec<u8> = (0..width * height * 4).map(|i| (i * 53 % 256) as u8).collect();
+
+        for out in [
+            resize_bilinear(&data, width, height, width, height),
+            resize_bilinear_linear(&data, width, height, width, height),
+        ] {
+            assert_eq!(out.len(), data.len());
+            for (a, b) in out.iter().zip(&data) {
+                assert!(a.abs_diff(*b) <= 1, "{:?} vs {:?}", out, data);
+            }
+        }
+    }
+
+    #[test]
+    fn crops_the_center() {
+        // 4x4 with pixel (x, y) = [x, y, 0, 255]
+        let data: Vec<u8> = (0..16u8).flat_map(|i| [i % 4, i / 4, 0, 255]).collect();
+
+        assert!(check_crop(4, 4, 1, 1, 2, 2).is_ok());
+        let out = crop_pixels(&data, 4, 1, 1, 2, 2);
+        assert_eq!(out, [[1, 1, 0, 255], [2, 1, 0, 255], [1, 2, 0, 255], [2, 2, 0, 255]].concat());
+    }
+
+    #[test]
+    fn rejects_crops_outside_the_image() {
+        assert!(check_crop(4, 4, 0, 0, 4, 4).is_ok());
+        assert_eq!(check_crop(4, 4, 1, 0, 4, 4).unwrap_err().code(), ErrorCode::OutOfBounds);
+        assert_eq!(check_crop(4, 4, 0, 3, 1, 2).unwrap_err().code(), ErrorCode::OutOfBounds);
+        assert_eq!(check_crop(4, 4, u32::MAX, 0, 2, 1).unwrap_err().code(), ErrorCode::OutOfBounds);
+        assert_eq!(check_crop(4, 4, 0, 0, 0, 2).unwrap_err().code(), ErrorCode::InvalidDimensions);
+    }
+
+    // 2 wide, 3 high, pixel n is [n, n, n, 255]:
+    //   0 1
+    //   2 3
+    //   4 5
+    fn labels(data: &[u8]) -> Vec<u8> {
+        data.chunks_exact(4).map(|px| px[0]).collect()
+    }
+
+    #[test]
+    fn remaps_pixels_of_a_2x3_image() {
+        let data: Vec<u8> = (0..6u8).flat_map(|n| [n, n, n, 255]).collect();
+
+        assert_eq!(labels(&flip_horizontal_pixels(&data, 2, 3)), [1, 0, 3, 2, 5, 4]);
+        assert_eq!(labels(&flip_vertical_pixels(&data, 2, 3)), [4, 5, 2, 3, 0, 1]);
+
+        // rotated output is 3 wide and 2 high, read row by row:
+        //   clockwise   4 2 0      counter   1 3 5
+        //               5 3 1                0 2 4
+        let (cw, w, h) = rotate_90_pixels(&data, 2, 3, true);
+        assert_eq!((w, h), (3, 2));
+        assert_eq!(labels(&cw), [4, 2, 0, 5, 3, 1]);
+        let (ccw, w, h) = rotate_90_pixels(&data, 2, 3, false);
+        assert_eq!((w, h), (3, 2));
+        assert_eq!(labels(&ccw), [1, 3, 5, 0, 2, 4]);
+    }
+}
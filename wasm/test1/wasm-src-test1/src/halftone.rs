@@ -0,0 +1,186 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DotShape {
+    Circle = 0,
+    Square = 1,
+}
+
+// classic print screen angles relative to `angle_deg`, cyan/magenta/yellow/black
+const CMYK_ANGLES: [f32; 4] = [15.0, 75.0, 0.0, 45.0];
+
+// print-style dot screen. the grid is rotated by angle_deg, each cell gets one
+// dot whose area matches the average ink (darkness) under it, so a 50% gray
+// covers half the cell. dot edges get partial coverage for antialiasing.
+// cmyk splits the image into four inks on their own screen angles and stacks
+// them multiplicatively on white paper, otherwise it's black ink only
+#[wasm_bindgen]
+pub fn halftone(
+    image_data: &ImageData,
+    cell_size: u32,
+    angle_deg: f32,
+    shape: DotShape,
+    cmyk: bool,
+) -> Result<ImageData, JsValue> {
+    if cell_size < 2 {
+        return Err(error(ErrorCode::InvalidParameter, "cell_size must be at least 2"));
+    }
+    if !angle_deg.is_finite() {
+        return Err(error(ErrorCode::InvalidParameter, "angle must be finite"));
+    }
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let mut data = image_data.data().0;
+    let screen = Screen { width, height, cell: cell_size as f32, shape };
+
+    if cmyk {
+        let mut inks = [const { Vec::new() }; 4];
+        for px in data.chunks_exact(4) {
+            let [c, m, y, k] = rgb_to_cmyk(px[0], px[1], px[2]);
+            for (ink, v) in inks.iter_mut().zip([c, m, y, k]) {
+                ink.push(v);
+            }
+        }
+        let coverage: Vec<Vec<f32>> = inks
+            .iter()
+            .zip(CMYK_ANGLES)
+            .map(|(ink, offset)| screen.coverage(ink, angle_deg + offset))
+            .collect();
+
+        for (i, px) in data.chunks_exact_mut(4).enumerate() {
+            let paper = 1.0 - coverage[3][i];
+            // cyan absorbs red, magenta green, yellow blue
+            for (c, value) in px[..3].iter_mut().enumerate() {
+                *value = (255.0 * paper * (1.0 - coverage[c][i])).round() as u8;
+            }
+        }
+    } else {
+        let ink: Vec<u8> = data
+            .chunks_exact(4)
+            .map(|px| 255 - (0.299 * px[0] as f64 + 0.587 * px[1] as f64 + 0.114 * px[2] as f64).round() as u8)
+            .collect();
+        let coverage = screen.coverage(&ink, angle_deg);
+
+        for (px, cov) in data.chunks_exact_mut(4).zip(coverage) {
+            let value = (255.0 * (1.0 - cov)).round() as u8;
+            px[0] = value;
+            px[1] = value;
+            px[2] = value;
+        }
+    }
+    // alpha is kept throughout
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        width as u32,
+        height as u32,
+    )
+}
+
+struct Screen {
+    width: usize,
+    height: usize,
+    cell: f32,
+    shape: DotShape,
+}
+
+impl Screen {
+    // per-pixel dot coverage 0..1 for one ink plane (0 = no ink, 255 = solid)
+    fn coverage(&self, ink: &[u8], angle_deg: f32) -> Vec<f32> {
+        let (width, height, cell) = (self.width, self.height, self.cell);
+        let means = CellMeans::new(ink, width, height, cell as usize);
+        let (sin, cos) = angle_deg.to_radians().sin_cos();
+
+        let mut out = vec![0f32; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                // pixel center in screen (rotated) space
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let u = px * cos + py * sin;
+                let v = -px * sin + py * cos;
+                let (cu, cv) = ((u / cell).floor(), (v / cell).floor());
+
+                // big dots spill into neighbouring cells, so check those too
+                let mut cov = 0f32;
+                for dv in -1..=1 {
+                    for du in -1..=1 {
+                        let center_u = (cu + du as f32 + 0.5) * cell;
+                        let center_v = (cv + dv as f32 + 0.5) * cell;
+                        // back to image space to read the ink under this cell
+                        let ix = center_u * cos - center_v * sin;
+                        let iy = center_u * sin + center_v * cos;
+                        let amount = means.at(ix, iy) / 255.0;
+                        if amount <= 0.0 {
+                            continue;
+                        }
+
+                        let (ou, ov) = (u - center_u, v - center_v);
+                        // dot area = amount * cell^2
+                        let edge = match self.shape {
+                            DotShape::Circle => cell * (amount / std::f32::consts::PI).sqrt() - ou.hypot(ov),
+                            DotShape::Square => cell * amount.sqrt() / 2.0 - ou.abs().max(ov.abs()),
+                        };
+                        // signed distance to the dot edge, +-0.5px ramp
+                        cov = cov.max((edge + 0.5).clamp(0.0, 1.0));
+                    }
+                }
+                out[y * width + x] = cov;
+            }
+        }
+        out
+    }
+}
+
+// average ink in a cell sized box around any point, via a summed-area table
+struct CellMeans {
+    table: Vec<u64>,
+    width: usize,
+    height: usize,
+    half: usize,
+}
+
+impl CellMeans {
+    fn new(ink: &[u8], width: usize, height: usize, cell: usize) -> CellMeans {
+        let stride = width + 1;
+        let mut table = vec![0u64; stride * (height + 1)];
+        for y in 0..height {
+            let mut row = 0u64;
+            for x in 0..width {
+                row += ink[y * width + x] as u64;
+                table[(y + 1) * stride + x + 1] = table[y * stride + x + 1] + row;
+            }
+        }
+        CellMeans { table, width, height, half: cell / 2 }
+    }
+
+    // cells hanging off the image read the nearest pixels instead
+    fn at(&self, x: f32, y: f32) -> f32 {
+        let cx = (x.max(0.0) as usize).min(self.width - 1);
+        let cy = (y.max(0.0) as usize).min(self.height - 1);
+        let x0 = cx.saturating_sub(self.half);
+        let y0 = cy.saturating_sub(self.half);
+        let x1 = (cx + self.half + 1).min(self.width);
+        let y1 = (cy + self.half + 1).min(self.height);
+
+        let stride = self.width + 1;
+        let sum = self.table[y1 * stride + x1] + self.table[y0 * stride + x0]
+            - self.table[y0 * stride + x1]
+            - self.table[y1 * stride + x0];
+        sum as f32 / ((x1 - x0) * (y1 - y0)) as f32
+    }
+}
+
+// naive (no ICC) separation with full gray component replacement
+fn rgb_to_cmyk(r: u8, g: u8, b: u8) -> [u8; 4] {
+    let max = r.max(g).max(b);
+    let k = 255 - max;
+    if max == 0 {
+        return [0, 0, 0, 255];
+    }
+    let ink = |v: u8| ((max - v) as u32 * 255 / max as u32) as u8;
+    [ink(r), ink(g), ink(b), k]
+}
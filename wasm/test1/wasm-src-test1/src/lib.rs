@@ -1,10 +1,88 @@
 use wasm_bindgen::prelude::*;
 use web_sys::ImageData;
 
+mod ascii;
+mod blend;
+mod buffer;
+mod channels;
+mod chroma;
+mod color;
+mod compare;
+mod dither;
+mod effects;
+mod fill;
+mod geometry;
+mod halftone;
+mod lut3d;
+mod motion;
+mod pixel_sort;
+mod rng;
+mod simd;
+mod tone;
+mod white_balance;
+
 #[wasm_bindgen]
 pub fn invert_colors(image_data: ImageData) -> Result<ImageData, JsValue> {
     let mut data = image_data.data().0;
+    invert_pixels(&mut data);
     
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+// darkroom solarization: channels at or above threshold get inverted
+#[wasm_bindgen]
+pub fn solarize(image_data: ImageData, threshold: u8) -> Result<ImageData, JsValue> {
+    let mut data = image_data.data().0;
+    solarize_pixels(&mut data, threshold);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+// pure black/white, white where luminance is at or above threshold
+#[wasm_bindgen]
+pub fn binarize(image_data: ImageData, threshold: u8) -> Result<ImageData, JsValue> {
+    let mut data = image_data.data().0;
+    binarize_pixels(&mut data, threshold);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+// rec.601 luma of the premultiplied color: rgb is scaled by alpha / 255 first,
+// so a pixel counts only as much as it covers and fully transparent ones come
+// out 0 whatever rgb they carry (PNG sprites often keep junk there, which
+// shows up as halos in a plain grayscale). that's the sprite composited over
+// black, so the result is opaque. e.g. red at alpha 128 -> 76 * 128 / 255 = 38
+#[wasm_bindgen]
+pub fn grayscale_premult(image_data: ImageData) -> Result<ImageData, JsValue> {
+    let mut data = image_data.data().0;
+    grayscale_premult_pixels(&mut data);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+// byte level cores, operate on the raw RGBA buffer in place
+
+fn invert_pixels(data: &mut [u8]) {
+    // the simd path does whole vectors, the loop below picks up the rest
+    #[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+    let data = simd::invert_pixels(data);
+
     // RGBA format: skip by 4 bytes
     for i in (0..data.len()).step_by(4) {
         data[i] = 255 - data[i];         // Red
@@ -12,10 +90,93 @@ pub fn invert_colors(image_data: ImageData) -> Result<ImageData, JsValue> {
         data[i + 2] = 255 - data[i + 2]; // Blue
         // leave alpha alone    
     }
-    
-    ImageData::new_with_u8_clamped_array_and_sh(
-        wasm_bindgen::Clamped(&data),
-        image_data.width(),
-        image_data.height(),
-    )
-}
\ No newline at end of file
+}
+
+fn solarize_pixels(data: &mut [u8], threshold: u8) {
+    for px in data.chunks_exact_mut(4) {
+        for value in px.iter_mut().take(3) {
+            if *value >= threshold {
+                *value = 255 - *value;
+            }
+        }
+    }
+}
+
+fn binarize_pixels(data: &mut [u8], threshold: u8) {
+    for px in data.chunks_exact_mut(4) {
+        // rec.601 luma
+        let luma = (0.299 * px[0] as f64 + 0.587 * px[1] as f64 + 0.114 * px[2] as f64).round();
+        let value = if luma >= threshold as f64 { 255 } else { 0 };
+        px[0] = value;
+        px[1] = value;
+        px[2] = value;
+    }
+}
+
+fn grayscale_premult_pixels(data: &mut [u8]) {
+    for px in data.chunks_exact_mut(4) {
+        let alpha = px[3] as f64 / 255.0;
+        let luma = 0.299 * px[0] as f64 * alpha + 0.587 * px[1] as f64 * alpha + 0.114 * px[2] as f64 * alpha;
+        let value = luma.round() as u8;
+        px[0] = value;
+        px[1] = value;
+        px[2] = value;
+        px[3] = 255;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // one gray pixel per value 0..=255, alpha varies to check it's left alone
+    fn gray_ramp() -> Vec<u8> {
+        (0..=255u8).flat_map(|v| [v, v, v, v / 2 + 1]).collect()
+    }
+
+    #[test]
+    fn solarize_flips_at_threshold() {
+        let mut data = gray_ramp();
+        solarize_pixels(&mut data, 100);
+
+        for (v, px) in data.chunks_exact(4).enumerate() {
+            let expected = if v >= 100 { 255 - v as u8 } else { v as u8 };
+            assert_eq!(px, [expected, expected, expected, v as u8 / 2 + 1], "value {}", v);
+        }
+    }
+
+    #[test]
+    fn binarize_flips_at_threshold() {
+        let mut data = gray_ramp();
+        binarize_pixels(&mut data, 100);
+
+        for (v, px) in data.chunks_exact(4).enumerate() {
+            let expected = if v >= 100 { 255 } else { 0 };
+            assert_eq!(px, [expected, expected, expected, v as u8 / 2 + 1], "value {}", v);
+        }
+    }
+
+    #[test]
+    fn solarize_at_128_folds_the_top_half_down() {
+        let mut data = gray_ramp();
+        solarize_pixels(&mut data, 128);
+
+        // 127 stays, 128 becomes 127, 255 goes to black: nothing ends up above 127
+        assert_eq!(data[127 * 4], 127);
+        assert_eq!(data[128 * 4], 127);
+        assert_eq!(data[255 * 4], 0);
+        assert!(data.chunks_exact(4).all(|px| px[0] <= 127));
+    }
+
+    #[test]
+    fn grayscale_premult_weights_by_alpha() {
+        // half transparent red, then the same red opaque, then transparent junk
+        let mut data = vec![255, 0, 0, 128, 255, 0, 0, 255, 200, 90, 30, 0];
+        grayscale_premult_pixels(&mut data);
+
+        // 0.299 * 255 * 128 / 255 = 38.3
+        assert_eq!(&data[0..4], &[38, 38, 38, 255]);
+        assert_eq!(&data[4..8], &[76, 76, 76, 255]);
+        assert_eq!(&data[8..12], &[0, 0, 0, 255]);
+    }
+}
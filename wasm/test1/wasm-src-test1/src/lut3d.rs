@@ -0,0 +1,122 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+// real .cube files top out at 65 or so, this is only there so a bogus header
+// can't ask for gigabytes up front
+const MAX_CUBE_SIZE: usize = 256;
+
+// 3D LUT color grading. `lut` is size^3 rgb triples (0..1) in .cube order,
+// red changing fastest, then green, then blue. colors between lattice points
+// are trilinearly interpolated
+#[wasm_bindgen]
+pub fn apply_lut3d(image_data: &ImageData, lut: &[f32], size: u32) -> Result<ImageData, JsValue> {
+    if size < 2 {
+        return Err(error(ErrorCode::InvalidParameter, "3d lut size must be at least 2"));
+    }
+    let n = size as usize;
+    // a huge size would wrap around and could match a short lut by accident
+    let expected = n.checked_mul(n).and_then(|v| v.checked_mul(n)).and_then(|v| v.checked_mul(3));
+    if expected != Some(lut.len()) {
+        return Err(error(ErrorCode::InvalidLength, "3d lut length must be size^3 * 3"));
+    }
+
+    let mut data = image_data.data().0;
+    let scale = (n - 1) as f32 / 255.0;
+
+    for px in data.chunks_exact_mut(4) {
+        let rgb = sample_lut3d(lut, n, [px[0] as f32 * scale, px[1] as f32 * scale, px[2] as f32 * scale]);
+        for (value, c) in px.iter_mut().zip(rgb.iter()) {
+            *value = (c * 255.0).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+// parses a .cube file into the flat array apply_lut3d wants. the size is
+// cbrt(length / 3) on the JS side. only 3D LUTs with the default 0..1 domain
+#[wasm_bindgen]
+pub fn parse_cube(text: &str) -> Result<Vec<f32>, JsValue> {
+    let mut size: Option<usize> = None;
+    let mut values: Vec<f32> = Vec::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let first = parts.next().unwrap_or("");
+        match first {
+            "LUT_3D_SIZE" => {
+                let n: usize = parts
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| error(ErrorCode::ParseError, "invalid LUT_3D_SIZE"))?;
+                if n < 2 {
+                    return Err(error(ErrorCode::ParseError, "LUT_3D_SIZE must be at least 2"));
+                }
+                let len = (n <= MAX_CUBE_SIZE)
+                    .then(|| n.checked_mul(n)?.checked_mul(n)?.checked_mul(3))
+                    .flatten()
+                    .ok_or_else(|| error(ErrorCode::ParseError, "LUT_3D_SIZE must be at most 256"))?;
+                size = Some(n);
+                values.reserve(len);
+            }
+            "LUT_1D_SIZE" => return Err(error(ErrorCode::ParseError, "1D .cube luts are not supported")),
+            "DOMAIN_MIN" | "DOMAIN_MAX" => {
+                let expected = if first == "DOMAIN_MIN" { 0.0 } else { 1.0 };
+                if !parts.all(|v| v.parse::<f32>().ok() == Some(expected)) {
+                    return Err(error(ErrorCode::ParseError, "only the default 0..1 cube domain is supported"));
+                }
+            }
+            // other keywords (TITLE etc.) don't affect the data
+            _ if first.starts_with(|c: char| c.is_ascii_alphabetic()) => {}
+            _ => {
+                let row: Vec<f32> = line
+                    .split_whitespace()
+                    .map(|v| v.parse::<f32>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| error(ErrorCode::ParseError, "invalid number in .cube data"))?;
+                if row.len() != 3 {
+                    return Err(error(ErrorCode::ParseError, ".cube data lines need exactly 3 values"));
+                }
+                values.extend_from_slice(&row);
+            }
+        }
+    }
+
+    let n = size.ok_or_else(|| error(ErrorCode::ParseError, "missing LUT_3D_SIZE"))?;
+    if values.len() != n * n * n * 3 {
+        return Err(error(ErrorCode::ParseError, ".cube data doesn't match LUT_3D_SIZE"));
+    }
+    Ok(values)
+}
+
+// pos is in lattice units (0..size-1) per channel
+fn sample_lut3d(lut: &[f32], n: usize, pos: [f32; 3]) -> [f32; 3] {
+    let i0 = pos.map(|p| (p.floor() as usize).min(n - 2));
+    let t = [pos[0] - i0[0] as f32, pos[1] - i0[1] as f32, pos[2] - i0[2] as f32];
+
+    let at = |r: usize, g: usize, b: usize, c: usize| lut[((b * n + g) * n + r) * 3 + c];
+
+    let mut out = [0f32; 3];
+    for (c, value) in out.iter_mut().enumerate() {
+        let [r, g, b] = i0;
+        // interpolate along red, then green, then blue
+        let c00 = at(r, g, b, c) + (at(r + 1, g, b, c) - at(r, g, b, c)) * t[0];
+        let c10 = at(r, g + 1, b, c) + (at(r + 1, g + 1, b, c) - at(r, g + 1, b, c)) * t[0];
+        let c01 = at(r, g, b + 1, c) + (at(r + 1, g, b + 1, c) - at(r, g, b + 1, c)) * t[0];
+        let c11 = at(r, g + 1, b + 1, c) + (at(r + 1, g + 1, b + 1, c) - at(r, g + 1, b + 1, c)) * t[0];
+        let c0 = c00 + (c10 - c00) * t[1];
+        let c1 = c01 + (c11 - c01) * t[1];
+        *value = c0 + (c1 - c0) * t[2];
+    }
+    out
+}
@@ -0,0 +1,202 @@
+use js_sys::Uint8ClampedArray;
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+#[wasm_bindgen]
+extern "C" {
+    // an ImageData seen as its JS object, for reading `data` as the JS array
+    // itself. web_sys's `data()` copies it into a fresh Vec, a frame-sized
+    // allocation on every call
+    type RawImageData;
+
+    #[wasm_bindgen(method, getter)]
+    fn data(this: &RawImageData) -> Uint8ClampedArray;
+}
+
+#[wasm_bindgen]
+pub struct MotionResult {
+    mask: ImageData,
+    changed_pixels: u32,
+    bounding_box: Option<[u32; 4]>,
+}
+
+#[wasm_bindgen]
+impl MotionResult {
+    // white where something moved, black elsewhere, opaque
+    #[wasm_bindgen(getter)]
+    pub fn mask(&self) -> ImageData {
+        self.mask.clone()
+    }
+
+    // white pixels in the mask, after dilation
+    #[wasm_bindgen(getter)]
+    pub fn changed_pixels(&self) -> u32 {
+        self.changed_pixels
+    }
+
+    // [x, y, width, height] around the motion, undefined if nothing moved
+    #[wasm_bindgen(getter)]
+    pub fn bounding_box(&self) -> Option<Vec<u32>> {
+        self.bounding_box.map(|b| b.to_vec())
+    }
+}
+
+// frame differencing for a webcam loop: every frame is compared against the
+// one before it. all buffers are allocated once in `new` and reused, only the
+// ImageData handed back to JS is new each frame
+//
+//   const detector = new MotionDetector(video.videoWidth, video.videoHeight);
+//   detector.threshold = 30;
+//   const { mask, bounding_box } = detector.process(frame);
+#[wasm_bindgen]
+pub struct MotionDetector {
+    width: usize,
+    height: usize,
+    threshold: u8,
+    dilate: u32,
+    has_previous: bool,
+    previous: Vec<u8>,
+    current: Vec<u8>,
+    mask: Vec<u8>,
+    scratch: Vec<u8>,
+    output: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl MotionDetector {
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, height: u32) -> MotionDetector {
+        let pixels = width as usize * height as usize;
+        MotionDetector {
+            width: width as usize,
+            height: height as usize,
+            threshold: 25,
+            dilate: 1,
+            has_previous: false,
+            previous: vec![0; pixels * 4],
+            current: vec![0; pixels * 4],
+            mask: vec![0; pixels],
+            scratch: vec![0; pixels],
+            output: vec![0; pixels * 4],
+        }
+    }
+
+    // a pixel moved if some channel changed by more than this, default 25.
+    // keeps sensor noise from lighting up the whole mask
+    #[wasm_bindgen(getter)]
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_threshold(&mut self, threshold: u8) {
+        self.threshold = threshold;
+    }
+
+    // grows the mask by this many pixels in every direction to join up the
+    // speckle into blobs, 0 turns it off. default 1
+    #[wasm_bindgen(getter)]
+    pub fn dilate(&self) -> u32 {
+        self.dilate
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_dilate(&mut self, dilate: u32) {
+        self.dilate = dilate;
+    }
+
+    // forget the stored frame, e.g. after switching cameras
+    pub fn reset(&mut self) {
+        self.has_previous = false;
+    }
+
+    // diffs against the previous frame and then stores this one. the very
+    // first frame has nothing to compare with and gives an empty mask
+    pub fn process(&mut self, frame: &ImageData) -> Result<MotionResult, JsValue> {
+        if frame.width() as usize != self.width || frame.height() as usize != self.height {
+            return Err(error(ErrorCode::InvalidDimensions, "frame size doesn't match the detector"));
+        }
+        // same size as `current` after the check above
+        frame.unchecked_ref::<RawImageData>().data().copy_to(&mut self.current);
+
+        if self.has_previous {
+            for ((m, a), b) in self.mask.iter_mut().zip(self.current.chunks_exact(4)).zip(self.previous.chunks_exact(4)) {
+                let max_diff = a[0].abs_diff(b[0]).max(a[1].abs_diff(b[1])).max(a[2].abs_diff(b[2]));
+                *m = (max_diff > self.threshold) as u8;
+            }
+            if self.dilate > 0 {
+                self.dilate_mask();
+            }
+        } else {
+            self.mask.fill(0);
+        }
+        // this frame is the next one's previous, the old previous gets overwritten then
+        std::mem::swap(&mut self.previous, &mut self.current);
+        self.has_previous = true;
+
+        let mut changed = 0u32;
+        // min x, min y, max x, max y
+        let mut bounds: Option<[usize; 4]> = None;
+        for (i, (&m, px)) in self.mask.iter().zip(self.output.chunks_exact_mut(4)).enumerate() {
+            let value = m * 255;
+            px[0] = value;
+            px[1] = value;
+            px[2] = value;
+            px[3] = 255;
+
+            if m != 0 {
+                let (x, y) = (i % self.width, i / self.width);
+                changed += 1;
+                bounds = Some(match bounds {
+                    None => [x, y, x, y],
+                    Some([x0, y0, x1, y1]) => [x0.min(x), y0.min(y), x1.max(x), y1.max(y)],
+                });
+            }
+        }
+
+        Ok(MotionResult {
+            mask: ImageData::new_with_u8_clamped_array_and_sh(
+                wasm_bindgen::Clamped(&self.output),
+                self.width as u32,
+                self.height as u32,
+            )?,
+            changed_pixels: changed,
+            bounding_box: bounds.map(|[x0, y0, x1, y1]| {
+                [x0 as u32, y0 as u32, (x1 - x0 + 1) as u32, (y1 - y0 + 1) as u32]
+            }),
+        })
+    }
+}
+
+impl MotionDetector {
+    // square max filter, split into a horizontal and a vertical pass. each pass
+    // keeps a running count of set pixels in the window so the radius is free
+    fn dilate_mask(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let r = self.dilate as usize;
+        dilate_line(&self.mask, &mut self.scratch, width, height, 1, width, r);
+        dilate_line(&self.scratch, &mut self.mask, height, width, width, 1, r);
+    }
+}
+
+// dilates `lines` lines of `length` pixels. `step` is the index distance between
+// neighbours along a line, `line_step` between the starts of two lines
+fn dilate_line(src: &[u8], dst: &mut [u8], length: usize, lines: usize, step: usize, line_step: usize, r: usize) {
+    for line in 0..lines {
+        let base = line * line_step;
+        let at = |i: usize| src[base + i * step] as u32;
+
+        // window is [i - r, i + r] cut off at both ends
+        let mut count: u32 = (0..r.min(length)).map(at).sum();
+        for i in 0..length {
+            if i + r < length {
+                count += at(i + r);
+            }
+            if i > r {
+                count -= at(i - r - 1);
+            }
+            dst[base + i * step] = (count > 0) as u8;
+        }
+    }
+}
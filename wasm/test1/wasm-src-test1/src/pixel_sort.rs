@@ -0,0 +1,151 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Horizontal = 0, // sort along rows
+    Vertical = 1,   // sort along columns
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Luminance = 0,
+    Hue = 1, // 0..360 degrees scaled to 0..255, grays count as 0
+    Red = 2,
+    Green = 3,
+    Blue = 4,
+}
+
+// glitch-art pixel sorting. pixels whose key is inside low..=high form runs,
+// each run gets sorted by key (ascending, stable), pixels outside the band stay
+// where they are and split the runs
+#[wasm_bindgen]
+pub fn pixel_sort(
+    image_data: &ImageData,
+    direction: SortDirection,
+    key: SortKey,
+    threshold_low: u8,
+    threshold_high: u8,
+) -> Result<ImageData, JsValue> {
+    if threshold_low > threshold_high {
+        return Err(error(ErrorCode::InvalidParameter, "threshold_low must not be above threshold_high"));
+    }
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let mut data = image_data.data().0;
+
+    sort_pixels(&mut data, width, height, direction, key, threshold_low..=threshold_high);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        width as u32,
+        height as u32,
+    )
+}
+
+fn sort_pixels(
+    data: &mut [u8],
+    width: usize,
+    height: usize,
+    direction: SortDirection,
+    key: SortKey,
+    band: std::ops::RangeInclusive<u8>,
+) {
+    // (number of lines, line length, step between pixels in a line)
+    let (lines, length, step) = match direction {
+        SortDirection::Horizontal => (height, width, 1),
+        SortDirection::Vertical => (width, height, width),
+    };
+    let first = |line: usize| match direction {
+        SortDirection::Horizontal => line * width,
+        SortDirection::Vertical => line,
+    };
+
+    // line buffers are reused, nothing is allocated per run
+    let mut pixels: Vec<[u8; 4]> = Vec::with_capacity(length);
+    let mut keys: Vec<u8> = Vec::with_capacity(length);
+    let mut sorted: Vec<[u8; 4]> = vec![[0; 4]; length];
+
+    for line in 0..lines {
+        pixels.clear();
+        keys.clear();
+        for i in 0..length {
+            let idx = (first(line) + i * step) * 4;
+            let px = [data[idx], data[idx + 1], data[idx + 2], data[idx + 3]];
+            keys.push(sort_key(px, key));
+            pixels.push(px);
+        }
+
+        let mut start = 0;
+        while start < length {
+            if !band.contains(&keys[start]) {
+                start += 1;
+                continue;
+            }
+            let mut end = start;
+            while end < length && band.contains(&keys[end]) {
+                end += 1;
+            }
+            counting_sort(&pixels[start..end], &keys[start..end], &mut sorted[start..end]);
+            pixels[start..end].copy_from_slice(&sorted[start..end]);
+            start = end;
+        }
+
+        for (i, px) in pixels.iter().enumerate() {
+            let idx = (first(line) + i * step) * 4;
+            data[idx..idx + 4].copy_from_slice(px);
+        }
+    }
+}
+
+// keys are bytes, so a counting sort is linear and stable for free
+fn counting_sort(pixels: &[[u8; 4]], keys: &[u8], out: &mut [[u8; 4]]) {
+    let mut offsets = [0usize; 256];
+    for &k in keys {
+        offsets[k as usize] += 1;
+    }
+    let mut total = 0;
+    for slot in offsets.iter_mut() {
+        let count = *slot;
+        *slot = total;
+        total += count;
+    }
+    for (px, &k) in pixels.iter().zip(keys) {
+        out[offsets[k as usize]] = *px;
+        offsets[k as usize] += 1;
+    }
+}
+
+fn sort_key(px: [u8; 4], key: SortKey) -> u8 {
+    match key {
+        // rec.601 luma
+        SortKey::Luminance => (0.299 * px[0] as f64 + 0.587 * px[1] as f64 + 0.114 * px[2] as f64).round() as u8,
+        SortKey::Hue => hue_byte(px[0], px[1], px[2]),
+        SortKey::Red => px[0],
+        SortKey::Green => px[1],
+        SortKey::Blue => px[2],
+    }
+}
+
+fn hue_byte(r: u8, g: u8, b: u8) -> u8 {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    if max == min {
+        return 0;
+    }
+    let (r, g, b) = (r as f64, g as f64, b as f64);
+    let delta = max as f64 - min as f64;
+    let sector = if max as f64 == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max as f64 == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    // sector is 0..6, 6 would wrap back to red
+    ((sector / 6.0 * 256.0) as u32).min(255) as u8
+}
@@ -0,0 +1,61 @@
+use wasm_bindgen::prelude::*;
+
+// tiny seeded PRNG so effects are reproducible, same seed -> same output.
+// also exported so JS-side effects can share the same sequence
+#[wasm_bindgen]
+pub struct Rng {
+    state: u64,
+    // box-muller makes two values at a time, keep the second for the next call
+    spare: Option<f64>,
+}
+
+#[wasm_bindgen]
+impl Rng {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u64) -> Rng {
+        // splitmix64 scramble, xorshift gets stuck on an all-zero state
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Rng {
+            state: if z == 0 { 1 } else { z },
+            spare: None,
+        }
+    }
+
+    // xorshift64*, a BigInt on the JS side
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // plain number for JS, the high bits are the better ones
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    // uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        // top 53 bits fill the f64 mantissa exactly
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // standard normal (mean 0, stddev 1) via box-muller
+    pub fn next_gaussian(&mut self) -> f64 {
+        if let Some(value) = self.spare.take() {
+            return value;
+        }
+
+        // 1 - u keeps ln() away from 0
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        let r = (-2.0 * u1.ln()).sqrt();
+        let theta = 2.0 * std::f64::consts::PI * u2;
+
+        self.spare = Some(r * theta.sin());
+        r * theta.cos()
+    }
+}
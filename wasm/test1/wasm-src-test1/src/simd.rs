@@ -0,0 +1,23 @@
+// simd128 fast paths, see wasm_src_common for how they get picked. every fast
+// path gives exactly the same bytes as the scalar code it replaces
+
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+mod fast {
+    use std::arch::wasm32::*;
+
+    // 4 pixels per op: 255 - x is x ^ 255, and the alpha lanes xor with 0.
+    // hands back the pixels that didn't fill a whole vector
+    pub(crate) fn invert_pixels(data: &mut [u8]) -> &mut [u8] {
+        let mask = u32x4_splat(0x00FF_FFFF);
+        let mut chunks = data.chunks_exact_mut(16);
+        for chunk in &mut chunks {
+            let ptr = chunk.as_mut_ptr() as *mut v128;
+            // SAFETY: the chunk is exactly 16 bytes, wasm loads/stores don't need alignment
+            unsafe { v128_store(ptr, v128_xor(v128_load(ptr), mask)) };
+        }
+        chunks.into_remainder()
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+pub(crate) use fast::*;
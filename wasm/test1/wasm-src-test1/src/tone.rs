@@ -0,0 +1,424 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+use wasm_src_common::linear::{linear_to_srgb, srgb_to_linear};
+
+use crate::color::{apply_lut_rgb, channel_histograms};
+
+// out = 255 * (in / 255)^(1 / g), g > 1 brightens and g < 1 darkens
+#[wasm_bindgen]
+pub fn gamma(image_data: &ImageData, g: f32) -> Result<ImageData, JsValue> {
+    if !(g.is_finite() && g > 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "gamma must be a positive number"));
+    }
+
+    let mut data = image_data.data().0;
+    apply_lut_rgb(&mut data, &gamma_lut(g as f64));
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+// multiplies linear light by 2^stops, so +1 doubles the light like a camera would
+// (decodes sRGB first, scaling the encoded values would shift the midtones)
+#[wasm_bindgen]
+pub fn exposure(image_data: &ImageData, stops: f32) -> Result<ImageData, JsValue> {
+    if !stops.is_finite() {
+        return Err(error(ErrorCode::InvalidParameter, "exposure stops must be a finite number"));
+    }
+
+    let mut data = image_data.data().0;
+    apply_lut_rgb(&mut data, &exposure_lut(stops as f64));
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+fn gamma_lut(g: f64) -> [u8; 256] {
+    let inv = 1.0 / g;
+    std::array::from_fn(|i| (255.0 * (i as f64 / 255.0).powf(inv)).round() as u8)
+}
+
+fn exposure_lut(stops: f64) -> [u8; 256] {
+    let gain = 2f64.powf(stops);
+    std::array::from_fn(|i| {
+        let linear = srgb_to_linear(i as f64 / 255.0) * gain;
+        (linear_to_srgb(linear.min(1.0)) * 255.0).round() as u8
+    })
+}
+
+// photoshop style levels for one channel: in_black..in_white is stretched to
+// out_black..out_white with a midtone gamma in between
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct Levels {
+    in_black: u8,
+    in_white: u8,
+    gamma: f32,
+    out_black: u8,
+    out_white: u8,
+}
+
+#[wasm_bindgen]
+impl Levels {
+    #[wasm_bindgen(constructor)]
+    pub fn new(in_black: u8, in_white: u8, gamma: f32, out_black: u8, out_white: u8) -> Result<Levels, JsValue> {
+        if in_black >= in_white {
+            return Err(error(ErrorCode::InvalidParameter, "levels in_black must be below in_white"));
+        }
+        if !(gamma.is_finite() && gamma > 0.0) {
+            return Err(error(ErrorCode::InvalidParameter, "levels gamma must be a positive number"));
+        }
+
+        Ok(Levels {
+            in_black,
+            in_white,
+            gamma,
+            out_black,
+            out_white,
+        })
+    }
+}
+
+impl Levels {
+    fn lut(&self) -> [u8; 256] {
+        let in_range = (self.in_white - self.in_black) as f64;
+        // out_black == out_white just gives a flat channel, nothing divides by it
+        let out_range = self.out_white as f64 - self.out_black as f64;
+        let inv_gamma = 1.0 / self.gamma as f64;
+
+        let mut lut = [0u8; 256];
+        for (i, v) in lut.iter_mut().enumerate() {
+            let t = ((i as f64 - self.in_black as f64) / in_range).clamp(0.0, 1.0);
+            *v = (self.out_black as f64 + t.powf(inv_gamma) * out_range).round() as u8;
+        }
+        lut
+    }
+}
+
+#[wasm_bindgen]
+pub fn levels(
+    image_data: &ImageData,
+    in_black: u8,
+    in_white: u8,
+    gamma: f32,
+    out_black: u8,
+    out_white: u8,
+) -> Result<ImageData, JsValue> {
+    let lut = Levels::new(in_black, in_white, gamma, out_black, out_white)?.lut();
+
+    let mut data = image_data.data().0;
+    apply_lut_rgb(&mut data, &lut);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+// independent levels for r, g and b, e.g. to fix a color cast
+#[wasm_bindgen]
+pub fn levels_per_channel(
+    image_data: &ImageData,
+    red: &Levels,
+    green: &Levels,
+    blue: &Levels,
+) -> Result<ImageData, JsValue> {
+    let luts = [red.lut(), green.lut(), blue.lut()];
+
+    let mut data = image_data.data().0;
+    for px in data.chunks_exact_mut(4) {
+        for (value, lut) in px.iter_mut().zip(luts.iter()) {
+            *value = lut[*value as usize];
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+// which part of the image `curves` adjusts
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CurveChannel {
+    Rgb = 0,
+    R = 1,
+    G = 2,
+    B = 3,
+    Luminance = 4, // shifts all channels by the same amount, so hue/chroma stay put
+}
+
+// `points` is a flat [x0, y0, x1, y1, ...] list in 0..255, sorted by x.
+// (0, 0) and (255, 255) are added if the list doesn't start/end there.
+// the curve is a monotone cubic through the points, so it never overshoots
+#[wasm_bindgen]
+pub fn curves(image_data: &ImageData, points: &[f32], channel: CurveChannel) -> Result<ImageData, JsValue> {
+    let lut = curve_lut(points)?;
+    let mut data = image_data.data().0;
+
+    for px in data.chunks_exact_mut(4) {
+        match channel {
+            CurveChannel::Rgb => {
+                for value in px.iter_mut().take(3) {
+                    *value = lut[*value as usize].round() as u8;
+                }
+            }
+            CurveChannel::R | CurveChannel::G | CurveChannel::B => {
+                let c = channel as usize - 1;
+                px[c] = lut[px[c] as usize].round() as u8;
+            }
+            CurveChannel::Luminance => {
+                // rec.601 luma, chroma (channel - luma) is kept as is
+                let luma = 0.299 * px[0] as f64 + 0.587 * px[1] as f64 + 0.114 * px[2] as f64;
+                let delta = sample_lut(&lut, luma) - luma;
+                for value in px.iter_mut().take(3) {
+                    *value = (*value as f64 + delta).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+// bakes the control points into 256 float entries (fritsch-carlson monotone cubic)
+fn curve_lut(points: &[f32]) -> Result<[f64; 256], JsValue> {
+    if points.is_empty() || !points.len().is_multiple_of(2) {
+        return Err(error(ErrorCode::InvalidLength, "curve points must be a flat list of (x, y) pairs"));
+    }
+
+    let mut xs: Vec<f64> = Vec::with_capacity(points.len() / 2 + 2);
+    let mut ys: Vec<f64> = Vec::with_capacity(points.len() / 2 + 2);
+    for pair in points.chunks_exact(2) {
+        let (x, y) = (pair[0] as f64, pair[1] as f64);
+        if !(0.0..=255.0).contains(&x) || !(0.0..=255.0).contains(&y) {
+            return Err(error(ErrorCode::InvalidParameter, "curve points must be within 0..255"));
+        }
+        if xs.last().is_some_and(|&last| x <= last) {
+            return Err(error(ErrorCode::InvalidParameter, "curve points must be sorted by x with no duplicates"));
+        }
+        xs.push(x);
+        ys.push(y);
+    }
+    if xs[0] > 0.0 {
+        xs.insert(0, 0.0);
+        ys.insert(0, 0.0);
+    }
+    if xs[xs.len() - 1] < 255.0 {
+        xs.push(255.0);
+        ys.push(255.0);
+    }
+
+    let n = xs.len();
+    let secants: Vec<f64> = (0..n - 1).map(|i| (ys[i + 1] - ys[i]) / (xs[i + 1] - xs[i])).collect();
+
+    // tangents, zeroed/limited where needed so the curve stays monotone
+    let mut tangents = vec![0f64; n];
+    tangents[0] = secants[0];
+    tangents[n - 1] = secants[n - 2];
+    for i in 1..n - 1 {
+        tangents[i] = if secants[i - 1] * secants[i] <= 0.0 {
+            0.0
+        } else {
+            (secants[i - 1] + secants[i]) / 2.0
+        };
+    }
+    for i in 0..n - 1 {
+        if secants[i] == 0.0 {
+            tangents[i] = 0.0;
+            tangents[i + 1] = 0.0;
+            continue;
+        }
+        let a = tangents[i] / secants[i];
+        let b = tangents[i + 1] / secants[i];
+        let len = a * a + b * b;
+        if len > 9.0 {
+            let t = 3.0 / len.sqrt();
+            tangents[i] = t * a * secants[i];
+            tangents[i + 1] = t * b * secants[i];
+        }
+    }
+
+    let mut lut = [0f64; 256];
+    let mut seg = 0;
+    for (i, value) in lut.iter_mut().enumerate() {
+        let x = i as f64;
+        while seg < n - 2 && x > xs[seg + 1] {
+            seg += 1;
+        }
+
+        // cubic hermite on the segment
+        let h = xs[seg + 1] - xs[seg];
+        let t = (x - xs[seg]) / h;
+        let t2 = t * t;
+        let t3 = t2 * t;
+        let y = (2.0 * t3 - 3.0 * t2 + 1.0) * ys[seg]
+            + (t3 - 2.0 * t2 + t) * h * tangents[seg]
+            + (-2.0 * t3 + 3.0 * t2) * ys[seg + 1]
+            + (t3 - t2) * h * tangents[seg + 1];
+        *value = y.clamp(0.0, 255.0);
+    }
+
+    Ok(lut)
+}
+
+// linear lookup between lut entries, for fractional inputs like luma
+fn sample_lut(lut: &[f64; 256], x: f64) -> f64 {
+    let x = x.clamp(0.0, 255.0);
+    let i = (x.floor() as usize).min(254);
+    let t = x - i as f64;
+    lut[i] + (lut[i + 1] - lut[i]) * t
+}
+
+// 768 counts: 256 bins each for red, then green, then blue
+#[wasm_bindgen]
+pub fn histogram(image_data: &ImageData) -> Vec<u32> {
+    channel_histograms(&image_data.data()).concat()
+}
+
+// stretches each channel so its darkest value becomes 0 and brightest 255.
+// clip_percent ignores that share of the darkest/brightest pixels (0.5 is a
+// good default) so a few stray hot pixels don't block the stretch.
+// a flat channel (min == max) is left as is
+#[wasm_bindgen]
+pub fn auto_levels(image_data: &ImageData, clip_percent: f32) -> Result<ImageData, JsValue> {
+    if !(0.0..50.0).contains(&clip_percent) {
+        return Err(error(ErrorCode::InvalidParameter, "clip_percent must be between 0 and 50"));
+    }
+
+    let mut data = image_data.data().0;
+    let hist = channel_histograms(&data);
+    let total = (data.len() / 4) as f64;
+    let clip = (total * clip_percent as f64 / 100.0).floor() as u64;
+
+    let mut luts = [[0u8; 256]; 3];
+    for (lut, channel) in luts.iter_mut().zip(hist.iter()) {
+        let (low, high) = clipped_range(channel, clip);
+        for (i, v) in lut.iter_mut().enumerate() {
+            *v = if low >= high {
+                i as u8
+            } else {
+                let t = (i as f64 - low as f64) / (high - low) as f64;
+                (t * 255.0).round().clamp(0.0, 255.0) as u8
+            };
+        }
+    }
+
+    for px in data.chunks_exact_mut(4) {
+        for (value, lut) in px.iter_mut().zip(luts.iter()) {
+            *value = lut[*value as usize];
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+// lowest and highest bins left after dropping `clip` pixels from each end
+fn clipped_range(hist: &[u32; 256], clip: u64) -> (usize, usize) {
+    let mut low = 0;
+    let mut seen = 0u64;
+    for (i, &count) in hist.iter().enumerate() {
+        seen += count as u64;
+        if seen > clip {
+            low = i;
+            break;
+        }
+    }
+
+    let mut high = 255;
+    seen = 0;
+    for (i, &count) in hist.iter().enumerate().rev() {
+        seen += count as u64;
+        if seen > clip {
+            high = i;
+            break;
+        }
+    }
+
+    (low, high)
+}
+
+// JS-built tone curves (film emulation etc.), one 256 entry table per channel
+#[wasm_bindgen]
+pub fn apply_lut(image_data: &ImageData, lut_r: &[u8], lut_g: &[u8], lut_b: &[u8]) -> Result<ImageData, JsValue> {
+    let luts = [to_lut(lut_r)?, to_lut(lut_g)?, to_lut(lut_b)?];
+
+    let mut data = image_data.data().0;
+    for px in data.chunks_exact_mut(4) {
+        for (value, lut) in px.iter_mut().zip(luts.iter()) {
+            *value = lut[*value as usize];
+        }
+        // alpha isn't zipped with a lut, so it passes through
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+// same table for r, g and b
+#[wasm_bindgen]
+pub fn apply_lut_combined(image_data: &ImageData, lut: &[u8]) -> Result<ImageData, JsValue> {
+    let lut = to_lut(lut)?;
+
+    let mut data = image_data.data().0;
+    apply_lut_rgb(&mut data, &lut);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+fn to_lut(values: &[u8]) -> Result<[u8; 256], JsValue> {
+    values
+        .try_into()
+        .map_err(|_| error(ErrorCode::InvalidLength, "lut must have exactly 256 entries"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every byte value in every channel, alpha included
+    fn all_values() -> Vec<u8> {
+        (0..=255u8).flat_map(|v| [v, 255 - v, v / 2, v]).collect()
+    }
+
+    #[test]
+    fn gamma_one_is_identity() {
+        let original = all_values();
+        let mut data = original.clone();
+        apply_lut_rgb(&mut data, &gamma_lut(1.0));
+        assert_eq!(data, original);
+    }
+
+    #[test]
+    fn zero_stops_is_identity() {
+        let original = all_values();
+        let mut data = original.clone();
+        apply_lut_rgb(&mut data, &exposure_lut(0.0));
+        assert_eq!(data, original);
+    }
+}
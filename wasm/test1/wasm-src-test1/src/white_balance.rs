@@ -0,0 +1,121 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+// temperature and tint are sliders in -100..100, 0/0 leaves the image alone.
+// temperature > 0 warms (towards orange), < 0 cools (towards blue): the gains
+// are the color of a blackbody at 6500K * 2^(-temperature / 100) relative to
+// 6500K, so the ends are ~3250K and ~13000K.
+// tint > 0 pushes towards magenta, < 0 towards green
+#[wasm_bindgen]
+pub fn white_balance(image_data: &ImageData, temperature: f32, tint: f32) -> Result<ImageData, JsValue> {
+    if !(-100.0..=100.0).contains(&temperature) || !(-100.0..=100.0).contains(&tint) {
+        return Err(error(ErrorCode::InvalidParameter, "temperature and tint must be between -100 and 100"));
+    }
+
+    let reference = blackbody_rgb(6500.0);
+    let target = blackbody_rgb(6500.0 * 2f64.powf(-temperature as f64 / 100.0));
+    let mut gains = [
+        target[0] / reference[0],
+        target[1] / reference[1],
+        target[2] / reference[2],
+    ];
+    gains[1] *= 1.0 - 0.3 * tint as f64 / 100.0;
+
+    let mut data = image_data.data().0;
+    apply_gains(&mut data, gains);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+// gray world: scales r, g and b so their means match. clip_percent skips the
+// brightest pixels (by luma) so blown highlights don't skew the means
+#[wasm_bindgen]
+pub fn auto_white_balance(image_data: &ImageData, clip_percent: f32) -> Result<ImageData, JsValue> {
+    if !(0.0..100.0).contains(&clip_percent) {
+        return Err(error(ErrorCode::InvalidParameter, "clip_percent must be between 0 and 100"));
+    }
+
+    let mut data = image_data.data().0;
+    let luma = |px: &[u8]| (0.299 * px[0] as f64 + 0.587 * px[1] as f64 + 0.114 * px[2] as f64).round() as usize;
+
+    // luma cutoff that leaves out clip_percent of the pixels
+    let mut hist = [0u64; 256];
+    for px in data.chunks_exact(4) {
+        hist[luma(px)] += 1;
+    }
+    let total = (data.len() / 4) as f64;
+    let skip = (total * clip_percent as f64 / 100.0).floor() as u64;
+    let mut cutoff = 255;
+    let mut seen = 0u64;
+    for (i, &count) in hist.iter().enumerate().rev() {
+        if seen + count > skip {
+            cutoff = i;
+            break;
+        }
+        seen += count;
+    }
+
+    let mut sums = [0f64; 3];
+    let mut count = 0u64;
+    for px in data.chunks_exact(4) {
+        if luma(px) <= cutoff {
+            sums[0] += px[0] as f64;
+            sums[1] += px[1] as f64;
+            sums[2] += px[2] as f64;
+            count += 1;
+        }
+    }
+
+    if count > 0 {
+        let means = sums.map(|s| s / count as f64);
+        let gray = (means[0] + means[1] + means[2]) / 3.0;
+        // an empty channel can't be scaled up to anything
+        let gains = means.map(|m| if m > 0.0 { gray / m } else { 1.0 });
+        apply_gains(&mut data, gains);
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+fn apply_gains(data: &mut [u8], gains: [f64; 3]) {
+    for px in data.chunks_exact_mut(4) {
+        for (value, gain) in px.iter_mut().zip(gains.iter()) {
+            *value = (*value as f64 * gain).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+// tanner helland's curve fit of blackbody color, good enough for 1000K-40000K
+fn blackbody_rgb(kelvin: f64) -> [f64; 3] {
+    let t = kelvin / 100.0;
+
+    let r = if t <= 66.0 {
+        255.0
+    } else {
+        329.698727446 * (t - 60.0).powf(-0.1332047592)
+    };
+    let g = if t <= 66.0 {
+        99.4708025861 * t.ln() - 161.1195681661
+    } else {
+        288.1221695283 * (t - 60.0).powf(-0.0755148492)
+    };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.5177312231 * (t - 10.0).ln() - 305.0447927307
+    };
+
+    // keep it positive so the gain ratios stay finite
+    [r, g, b].map(|c| c.clamp(1.0, 255.0))
+}
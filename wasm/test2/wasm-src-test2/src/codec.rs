@@ -0,0 +1,323 @@
+use std::io::Cursor;
+
+use js_sys::{Object, Reflect, Uint8Array};
+use jpeg_decoder::PixelFormat;
+use gif::{Frame, Repeat};
+use png::{BitDepth, ColorType, DeflateCompression, Encoder, Transformations};
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+
+use crate::error::{error, ErrorCode, ImageError};
+use crate::nearest::NearestCentroid;
+use crate::{find_nearest_centroid, rgb_pixels, train_centroids, train_centroids_with};
+
+// PNG bytes straight from WASM, so the page can make a download Blob without
+// going through a canvas. compression is 0 (stored) to 9 (smallest)
+#[wasm_bindgen]
+pub fn encode_png(image_data: &ImageData, compression: u8) -> Result<Vec<u8>, JsValue> {
+    let deflate = deflate_level(compression)?;
+    let data = image_data.data();
+
+    write_png(image_data.width(), image_data.height(), ColorType::Rgba, deflate, None, &data)
+}
+
+// PNG-8: every pixel becomes the index of its nearest palette color (rgb
+// triples, e.g. the centroids from `quantize`), written with a PLTE chunk.
+// about a quarter of the raw size before compression even starts.
+// alpha is dropped, the palette has no transparency
+#[wasm_bindgen]
+pub fn encode_png_indexed(image_data: &ImageData, palette: &[u8]) -> Result<Vec<u8>, JsValue> {
+    if palette.is_empty() || !palette.len().is_multiple_of(3) {
+        return Err(error(ErrorCode::InvalidLength, "palette must be a non-empty list of rgb triples"));
+    }
+    if palette.len() / 3 > 256 {
+        return Err(error(ErrorCode::InvalidLength, "PNG-8 palettes hold at most 256 colors"));
+    }
+
+    let centroids: Vec<[f64; 3]> = palette
+        .chunks_exact(3)
+        .map(|c| [c[0] as f64, c[1] as f64, c[2] as f64])
+        .collect();
+    let mut index = NearestCentroid::new(&centroids);
+    let indices: Vec<u8> = rgb_pixels(&image_data.data())
+        .iter()
+        .map(|p| index.find(p) as u8)
+        .collect();
+
+    write_png(
+        image_data.width(),
+        image_data.height(),
+        ColorType::Indexed,
+        DeflateCompression::Level(9),
+        Some(palette),
+        &indices,
+    )
+}
+
+// raw k-means output for a JS GIF / PNG-8 encoder, no recolored image to
+// re-scan. returns a plain object:
+//   indices: Uint8Array, width * height palette indices, row-major from the
+//            top-left, one byte per pixel
+//   palette: Uint8Array, k rgb triples packed r, g, b, r, g, b... so index i
+//            is palette[i * 3 .. i * 3 + 3]. empty for an empty image
+//   width, height
+// k is capped at 256 so every index fits a byte. alpha is dropped
+#[wasm_bindgen]
+pub fn quantize_indexed(image_data: &ImageData, k: usize) -> Result<JsValue, JsValue> {
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+    if k > 256 {
+        return Err(error(ErrorCode::InvalidK, "indexed output holds at most 256 colors"));
+    }
+
+    let pixels = rgb_pixels(&image_data.data());
+    let centroids = train_centroids(&pixels, k);
+    let mut index = NearestCentroid::new(&centroids);
+    let indices: Vec<u8> = pixels.iter().map(|p| index.find(p) as u8).collect();
+    let palette: Vec<u8> = centroids.iter().flat_map(|c| c.map(|v| v.round() as u8)).collect();
+
+    let result = Object::new();
+    Reflect::set(&result, &"indices".into(), &Uint8Array::from(&indices[..]))?;
+    Reflect::set(&result, &"palette".into(), &Uint8Array::from(&palette[..]))?;
+    Reflect::set(&result, &"width".into(), &image_data.width().into())?;
+    Reflect::set(&result, &"height".into(), &image_data.height().into())?;
+    Ok(result.into())
+}
+
+// animated GIF of k-means converging: one frame per iteration, each pixel
+// mapped to that iteration's centroids. GIF frames carry their own palette, so
+// the centroids go in as-is. loops forever, and training stops after at most
+// 20 iterations so the frame count is bounded. alpha is dropped
+#[wasm_bindgen]
+pub fn quantize_animation_gif(image_data: &ImageData, k: usize, frame_delay_ms: u16) -> Result<Vec<u8>, JsValue> {
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+    if k > 256 {
+        return Err(error(ErrorCode::InvalidK, "GIF palettes hold at most 256 colors"));
+    }
+    let (Ok(width), Ok(height)) = (u16::try_from(image_data.width()), u16::try_from(image_data.height())) else {
+        return Err(error(ErrorCode::InvalidDimensions, "GIF frames are limited to 65535x65535"));
+    };
+
+    let pixels = rgb_pixels(&image_data.data());
+    let mut frames: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+    train_centroids_with(&pixels, k, |_, _, centroids| {
+        let indices = pixels
+            .iter()
+            .map(|p| find_nearest_centroid(p, centroids) as u8)
+            .collect();
+        let palette = centroids.iter().flat_map(|c| c.map(|v| v.round() as u8)).collect();
+        frames.push((indices, palette));
+    });
+
+    let encode_error = |e: gif::EncodingError| error(ErrorCode::EncodeError, &e.to_string());
+    let mut bytes = Vec::new();
+    {
+        let mut encoder = gif::Encoder::new(&mut bytes, width, height, &[]).map_err(encode_error)?;
+        encoder.set_repeat(Repeat::Infinite).map_err(encode_error)?;
+
+        for (indices, palette) in frames {
+            let frame = Frame {
+                width,
+                height,
+                delay: gif_delay(frame_delay_ms),
+                palette: Some(palette),
+                buffer: indices.into(),
+                ..Frame::default()
+            };
+            encoder.write_frame(&frame).map_err(encode_error)?;
+        }
+    }
+
+    Ok(bytes)
+}
+
+// gif delays are in hundredths of a second. rounded to the nearest one and at
+// least 1, a 0 delay means something different in every viewer. the rounding
+// is done in u32 so 65535 ms can't overflow
+fn gif_delay(frame_delay_ms: u16) -> u16 {
+    let centiseconds = (frame_delay_ms as u32 + 5) / 10;
+    u16::try_from(centiseconds.max(1)).unwrap_or(u16::MAX)
+}
+
+fn deflate_level(compression: u8) -> Result<DeflateCompression, JsValue> {
+    match compression {
+        0 => Ok(DeflateCompression::NoCompression),
+        1..=9 => Ok(DeflateCompression::Level(compression)),
+        _ => Err(error(ErrorCode::InvalidParameter, "compression must be between 0 and 9")),
+    }
+}
+
+fn write_png(
+    width: u32,
+    height: u32,
+    color: ColorType,
+    deflate: DeflateCompression,
+    palette: Option<&[u8]>,
+    pixels: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let encode_error = |e: png::EncodingError| error(ErrorCode::EncodeError, &e.to_string());
+
+    let mut bytes = Vec::new();
+    let mut encoder = Encoder::new(&mut bytes, width, height);
+    encoder.set_color(color);
+    encoder.set_depth(BitDepth::Eight);
+    encoder.set_deflate_compression(deflate);
+    if let Some(palette) = palette {
+        encoder.set_palette(palette);
+    }
+
+    let mut writer = encoder.write_header().map_err(encode_error)?;
+    writer.write_image_data(pixels).map_err(encode_error)?;
+    writer.finish().map_err(encode_error)?;
+
+    Ok(bytes)
+}
+
+const PNG_MAGIC: &[u8] = b"\x89PNG\r\n\x1a\n";
+const JPEG_MAGIC: &[u8] = &[0xFF, 0xD8, 0xFF];
+
+// decodes dropped files without an <img> round-trip. the format is sniffed from
+// the magic bytes, not the file name. everything comes out as 8-bit RGBA:
+// 16-bit channels keep their high byte, gray and palette images are expanded
+#[wasm_bindgen]
+pub fn decode_image(bytes: &[u8]) -> Result<ImageData, JsValue> {
+    let (width, height, rgba) = decode_rgba(bytes)?;
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&rgba), width, height)
+}
+
+// everything but the ImageData, so it runs without a browser
+fn decode_rgba(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>), ImageError> {
+    if bytes.starts_with(PNG_MAGIC) {
+        decode_png(bytes)
+    } else if bytes.starts_with(JPEG_MAGIC) {
+        decode_jpeg(bytes)
+    } else {
+        Err(ImageError::new(ErrorCode::UnsupportedFormat, "only PNG and JPEG can be decoded"))
+    }
+}
+
+fn decode_png(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>), ImageError> {
+    let decode_error = |e: png::DecodingError| ImageError::new(ErrorCode::DecodeError, &e.to_string());
+
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    // palette -> rgb, tRNS -> alpha, low bit depths -> 8, 16 bit -> 8
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(decode_error)?;
+
+    let size = reader
+        .output_buffer_size()
+        .ok_or_else(|| ImageError::new(ErrorCode::DecodeError, "PNG is too large to decode"))?;
+    let mut buffer = vec![0u8; size];
+    let frame = reader.next_frame(&mut buffer).map_err(decode_error)?;
+    let pixels = &buffer[..frame.buffer_size()];
+
+    let rgba = match frame.color_type {
+        ColorType::Rgba => pixels.to_vec(),
+        ColorType::Rgb => pixels.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        ColorType::GrayscaleAlpha => pixels.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        ColorType::Grayscale => pixels.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+        // EXPAND turns indexed into rgb(a), so this doesn't come out of next_frame
+        ColorType::Indexed => return Err(ImageError::new(ErrorCode::DecodeError, "unexpanded palette PNG")),
+    };
+    Ok((frame.width, frame.height, rgba))
+}
+
+fn decode_jpeg(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>), ImageError> {
+    let mut decoder = jpeg_decoder::Decoder::new(Cursor::new(bytes));
+    let pixels = decoder
+        .decode()
+        .map_err(|e| ImageError::new(ErrorCode::DecodeError, &e.to_string()))?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| ImageError::new(ErrorCode::DecodeError, "JPEG has no frame header"))?;
+
+    let rgba = match info.pixel_format {
+        PixelFormat::RGB24 => pixels.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        PixelFormat::L8 => pixels.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+        // big endian, keep the high byte
+        PixelFormat::L16 => pixels.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], 255]).collect(),
+        PixelFormat::CMYK32 => pixels
+            .chunks_exact(4)
+            .flat_map(|p| {
+                let paper = 255 - p[3] as u32;
+                let channel = |c: u8| ((255 - c as u32) * paper / 255) as u8;
+                [channel(p[0]), channel(p[1]), channel(p[2]), 255]
+            })
+            .collect(),
+    };
+    Ok((info.width as u32, info.height as u32, rgba))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 3x2 8-bit gray: 0 128 255 / 10 20 30
+    const GRAY_PNG: &[u8] = include_bytes!("../testdata/gray.png");
+    // 3x2 palette of red, green, blue with blue at alpha 64 via tRNS
+    const PALETTE_PNG: &[u8] = include_bytes!("../testdata/palette.png");
+    // 16x8 baseline JFIF, left half (200, 30, 30) and right half (30, 60, 200)
+    const BASELINE_JPEG: &[u8] = include_bytes!("../testdata/baseline.jpg");
+
+    #[test]
+    fn decodes_grayscale_png() {
+        let (width, height, rgba) = decode_rgba(GRAY_PNG).unwrap();
+        assert_eq!((width, height), (3, 2));
+        let expected: Vec<u8> = [0, 128, 255, 10, 20, 30].iter().flat_map(|&v| [v, v, v, 255]).collect();
+        assert_eq!(rgba, expected);
+    }
+
+    #[test]
+    fn decodes_palette_png_with_transparency() {
+        let (width, height, rgba) = decode_rgba(PALETTE_PNG).unwrap();
+        assert_eq!((width, height), (3, 2));
+        let red = [255, 0, 0, 255];
+        let green = [0, 255, 0, 255];
+        let blue = [0, 0, 255, 64];
+        assert_eq!(rgba, [red, green, blue, blue, green, red].concat());
+    }
+
+    #[test]
+    fn decodes_baseline_jpeg() {
+        let (width, height, rgba) = decode_rgba(BASELINE_JPEG).unwrap();
+        assert_eq!((width, height), (16, 8));
+        assert_eq!(rgba.len(), 16 * 8 * 4);
+        // lossy, but flat blocks should come back within a few levels
+        for (i, px) in rgba.chunks_exact(4).enumerate() {
+            let expected = if i % 16 < 8 { [200, 30, 30] } else { [30, 60, 200] };
+            for c in 0..3 {
+                assert!((px[c] as i32 - expected[c]).abs() <= 3, "pixel {} is {:?}", i, px);
+            }
+            assert_eq!(px[3], 255);
+        }
+    }
+
+    #[test]
+    fn gif_delay_rounds_to_centiseconds() {
+        assert_eq!(gif_delay(0), 1);
+        assert_eq!(gif_delay(4), 1);
+        assert_eq!(gif_delay(15), 2);
+        assert_eq!(gif_delay(33), 3);
+        assert_eq!(gif_delay(35), 4);
+        assert_eq!(gif_delay(100), 10);
+        assert_eq!(gif_delay(u16::MAX), 6554);
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        let err = decode_rgba(b"GIF89a\x01\x00\x01\x00").unwrap_err();
+        assert_eq!(err.code(), ErrorCode::UnsupportedFormat);
+        let err = decode_rgba(&[]).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::UnsupportedFormat);
+    }
+
+    #[test]
+    fn truncated_png_is_a_decode_error() {
+        let err = decode_rgba(&GRAY_PNG[..40]).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::DecodeError);
+    }
+}
@@ -0,0 +1,118 @@
+use js_sys::{Function, Promise};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::ImageData;
+
+use crate::cancel::{self, CancellationToken};
+use crate::error::{error, ErrorCode};
+use crate::{remap_chunk, rgb_pixels, train_centroids_cancellable};
+
+// `quantize` that hands the main thread back between slices of work:
+//
+//   const out = await quantize_async(imageData, 8, 64, (done, total) => bar.value = done / total);
+//
+// training only looks at a 1000 pixel sample and runs in one go, the remap
+// over every pixel is what takes long, so that is done `chunk_rows` rows at a
+// time with a setTimeout(0) between chunks. that lets the browser paint and
+// handle input, a resolved promise wouldn't (microtasks run before rendering).
+// `progress(rows_done, height)` is called after every chunk, errors thrown by
+// it are ignored like in quantize_with_progress. the result is byte for byte
+// what `quantize` returns
+#[wasm_bindgen]
+pub async fn quantize_async(
+    image_data: ImageData,
+    k: usize,
+    chunk_rows: u32,
+    progress: Option<Function>,
+) -> Result<ImageData, JsValue> {
+    quantize_chunked(image_data, k, chunk_rows, progress, None).await
+}
+
+// `quantize_async` that rejects with CANCELLED when `cancel` is tripped, it's
+// checked before every chunk. this is where cancel() from a UI event handler
+// actually gets a chance to run in between
+#[wasm_bindgen]
+pub fn quantize_async_cancellable(
+    image_data: ImageData,
+    k: usize,
+    chunk_rows: u32,
+    progress: Option<Function>,
+    cancel: &CancellationToken,
+) -> Promise {
+    // the clone shares the flag, the future can't hold on to the JS handle
+    let cancel = cancel.clone();
+    future_to_promise(async move {
+        quantize_chunked(image_data, k, chunk_rows, progress, Some(cancel))
+            .await
+            .map(JsValue::from)
+    })
+}
+
+async fn quantize_chunked(
+    image_data: ImageData,
+    k: usize,
+    chunk_rows: u32,
+    progress: Option<Function>,
+    cancel: Option<CancellationToken>,
+) -> Result<ImageData, JsValue> {
+    let cancel = cancel.as_ref();
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+    if chunk_rows == 0 {
+        return Err(error(ErrorCode::InvalidParameter, "chunk_rows must be at least 1"));
+    }
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let data = image_data.data().0;
+
+    let pixels = rgb_pixels(&data);
+    let centroids = train_centroids_cancellable(&pixels, k, cancel);
+
+    let mut output = vec![0u8; data.len()];
+    // rows, not pixels, so progress maps onto what the user sees
+    let chunk = (chunk_rows as usize * width).max(1);
+    let mut rows_done = 0;
+    for ((output, data), pixels) in output
+        .chunks_mut(chunk * 4)
+        .zip(data.chunks(chunk * 4))
+        .zip(pixels.chunks(chunk))
+    {
+        cancel::check(cancel)?;
+        remap_chunk(data, pixels, &centroids, output);
+        rows_done = (rows_done + chunk_rows as usize).min(height);
+
+        if let Some(progress) = &progress {
+            let _ = progress.call2(
+                &JsValue::NULL,
+                &JsValue::from(rows_done as u32),
+                &JsValue::from(height as u32),
+            );
+        }
+        yield_to_event_loop().await;
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+// resolves on the next macrotask. setTimeout is looked up on the global so
+// this works in a worker too, without one it falls back to a plain resolved
+// promise
+pub(crate) async fn yield_to_event_loop() {
+    let promise = Promise::new(&mut |resolve, _| {
+        let set_timeout = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+            .ok()
+            .and_then(|f| f.dyn_into::<Function>().ok());
+        let _ = match set_timeout {
+            Some(set_timeout) => set_timeout.call2(&JsValue::NULL, &resolve, &JsValue::from(0)),
+            None => resolve.call0(&JsValue::NULL),
+        };
+    });
+    let _ = JsFuture::from(promise).await;
+}
@@ -0,0 +1,101 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::linear::{linear_to_srgb, srgb_to_linear};
+
+use crate::error::{error, ErrorCode};
+use crate::euclidean_distance;
+use crate::lab::srgb_to_lab;
+
+// color vision deficiency simulation (Viénot, Brettel & Mollon 1999).
+// linear rgb -> LMS cone space, the missing cone's response is rebuilt from
+// the other two, then back to rgb
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CvdKind {
+    Protanopia = 0,   // no L (red) cones
+    Deuteranopia = 1, // no M (green) cones
+    Tritanopia = 2,   // no S (blue) cones
+}
+
+const RGB_TO_LMS: [[f64; 3]; 3] = [
+    [17.8824, 43.5161, 4.11935],
+    [3.45565, 27.1554, 3.86714],
+    [0.0299566, 0.184309, 1.46709],
+];
+
+const LMS_TO_RGB: [[f64; 3]; 3] = [
+    [0.0809444479, -0.130504409, 0.116721066],
+    [-0.0102485335, 0.0540193266, -0.113614708],
+    [-0.000365296938, -0.00412161469, 0.693511405],
+];
+
+// severity 0..1 blends from the original (0) to full dichromacy (1)
+#[wasm_bindgen]
+pub fn simulate_cvd(image_data: &ImageData, kind: CvdKind, severity: f32) -> Result<ImageData, JsValue> {
+    if !(0.0..=1.0).contains(&severity) {
+        return Err(error(ErrorCode::InvalidParameter, "severity must be between 0 and 1"));
+    }
+
+    let mut data = image_data.data().0;
+    let severity = severity as f64;
+
+    for px in data.chunks_exact_mut(4) {
+        let rgb = simulate([px[0], px[1], px[2]], kind, severity);
+        px[..3].copy_from_slice(&rgb);
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        image_data.width(),
+        image_data.height(),
+    )
+}
+
+// smallest delta E (CIE76) between any two colors of a flat rgb palette once
+// it's seen through `kind`. below ~10 colors start to look the same
+#[wasm_bindgen]
+pub fn palette_cvd_check(palette: &[u8], kind: CvdKind) -> Result<f64, JsValue> {
+    if palette.len() < 6 || !palette.len().is_multiple_of(3) {
+        return Err(error(ErrorCode::InvalidLength, "palette must be at least two flat rgb triples"));
+    }
+
+    let simulated: Vec<[f64; 3]> = palette
+        .chunks_exact(3)
+        .map(|c| srgb_to_lab(simulate([c[0], c[1], c[2]], kind, 1.0)))
+        .collect();
+
+    let mut min_delta = f64::INFINITY;
+    for (i, a) in simulated.iter().enumerate() {
+        for b in &simulated[i + 1..] {
+            min_delta = min_delta.min(euclidean_distance(a, b));
+        }
+    }
+    Ok(min_delta)
+}
+
+fn simulate(rgb: [u8; 3], kind: CvdKind, severity: f64) -> [u8; 3] {
+    let linear = rgb.map(|c| srgb_to_linear(c as f64 / 255.0));
+    let [l, m, s] = mul(&RGB_TO_LMS, linear);
+
+    let lms = match kind {
+        CvdKind::Protanopia => [2.02344 * m - 2.52581 * s, m, s],
+        CvdKind::Deuteranopia => [l, 0.494207 * l + 1.24827 * s, s],
+        CvdKind::Tritanopia => [l, m, -0.395913 * l + 0.801109 * m],
+    };
+    let simulated = mul(&LMS_TO_RGB, lms);
+
+    let mut out = [0u8; 3];
+    for c in 0..3 {
+        let mixed = linear[c] + (simulated[c] - linear[c]) * severity;
+        out[c] = (linear_to_srgb(mixed.clamp(0.0, 1.0)) * 255.0).round() as u8;
+    }
+    out
+}
+
+fn mul(m: &[[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
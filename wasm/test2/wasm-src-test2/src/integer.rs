@@ -0,0 +1,103 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+
+use crate::error::{error, ErrorCode};
+
+// `quantize` with no floating point anywhere: same sample size, same
+// far-apart init and the same 20 iteration cap, but centroids are 0..255
+// integers, distances are squared integer distances and means round as
+// (sum + len / 2) / len. the output is bit-identical on every platform, which
+// makes it the one to assert against in tests. it can land on slightly
+// different colors than `quantize` since the means are rounded every iteration
+#[wasm_bindgen]
+pub fn quantize_integer(image_data: &ImageData, k: usize) -> Result<ImageData, JsValue> {
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let mut data = image_data.data().0;
+
+    let pixels: Vec<[u8; 3]> = data.chunks_exact(4).map(|px| [px[0], px[1], px[2]]).collect();
+    let centroids = train(&pixels, k);
+
+    // alpha stays where it is
+    for (px, pixel) in data.chunks_exact_mut(4).zip(&pixels) {
+        px[..3].copy_from_slice(&centroids[nearest(pixel, &centroids)]);
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&data), width, height)
+}
+
+fn train(pixels: &[[u8; 3]], k: usize) -> Vec<[u8; 3]> {
+    // evenly spaced 1000 pixel sample, integer index math instead of a float step
+    let sample_size = 1000.min(pixels.len());
+    let sample: Vec<[u8; 3]> = (0..sample_size).map(|i| pixels[i * pixels.len() / sample_size]).collect();
+    if sample.is_empty() {
+        return Vec::new();
+    }
+
+    // first centroid at the 1/4 mark, then repeatedly the pixel furthest from
+    // every centroid so far
+    let mut centroids = vec![sample[sample.len() / 4]];
+    for _ in 1..k {
+        let mut best = (0, 0);
+        for (i, pixel) in sample.iter().enumerate() {
+            let d = centroids.iter().map(|c| distance_sq(pixel, c)).min().unwrap_or(0);
+            if d > best.1 || i == 0 {
+                best = (i, d);
+            }
+        }
+        centroids.push(sample[best.0]);
+    }
+
+    for _ in 0..20 {
+        let mut sums = vec![[0u32; 4]; k];
+        for pixel in &sample {
+            let sum = &mut sums[nearest(pixel, &centroids)];
+            for c in 0..3 {
+                sum[c] += pixel[c] as u32;
+            }
+            sum[3] += 1;
+        }
+
+        // empty clusters keep their old centroid
+        let updated: Vec<[u8; 3]> = sums
+            .iter()
+            .zip(&centroids)
+            .map(|(s, &old)| {
+                let len = s[3];
+                if len == 0 {
+                    old
+                } else {
+                    [0, 1, 2].map(|c| ((s[c] + len / 2) / len) as u8)
+                }
+            })
+            .collect();
+
+        // integer means settle exactly, no tolerance needed
+        if updated == centroids {
+            break;
+        }
+        centroids = updated;
+    }
+
+    centroids
+}
+
+// lowest index wins ties
+fn nearest(pixel: &[u8; 3], centroids: &[[u8; 3]]) -> usize {
+    let mut best = (0, u32::MAX);
+    for (i, centroid) in centroids.iter().enumerate() {
+        let d = distance_sq(pixel, centroid);
+        if d < best.1 {
+            best = (i, d);
+        }
+    }
+    best.0
+}
+
+fn distance_sq(a: &[u8; 3], b: &[u8; 3]) -> u32 {
+    (0..3).map(|c| (a[c].abs_diff(b[c]) as u32).pow(2)).sum()
+}
@@ -0,0 +1,122 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::linear::{linear_to_srgb, srgb_to_linear};
+
+use crate::error::{error, ErrorCode};
+use crate::{find_nearest_centroid, train_centroids};
+
+// k-means in CIE L*a*b* instead of RGB. euclidean distance in lab roughly
+// matches perceived difference, so palettes come out better on skin and skies
+#[wasm_bindgen]
+pub fn quantize_lab(image_data: &ImageData, k: usize) -> Result<ImageData, JsValue> {
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let data = image_data.data();
+
+    let pixels: Vec<[f64; 3]> = data
+        .chunks_exact(4)
+        .map(|px| srgb_to_lab([px[0], px[1], px[2]]))
+        .collect();
+    let centroids = train_centroids(&pixels, k);
+    // convert the palette back once instead of per pixel
+    let palette: Vec<[u8; 3]> = centroids.iter().map(|c| lab_to_srgb(*c)).collect();
+
+    let mut output = vec![0u8; data.len()];
+    for (i, pixel) in pixels.iter().enumerate() {
+        let [r, g, b] = palette[find_nearest_centroid(pixel, &centroids)];
+
+        output[i * 4] = r;
+        output[i * 4 + 1] = g;
+        output[i * 4 + 2] = b;
+        output[i * 4 + 3] = data[i * 4 + 3];
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&output), width, height)
+}
+
+// D65 reference white
+const WHITE: [f64; 3] = [0.95047, 1.0, 1.08883];
+
+// sRGB -> linear -> XYZ -> Lab
+pub(crate) fn srgb_to_lab(rgb: [u8; 3]) -> [f64; 3] {
+    let [r, g, b] = rgb.map(|c| srgb_to_linear(c as f64 / 255.0));
+
+    let x = 0.4124564 * r + 0.3575761 * g + 0.1804375 * b;
+    let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+    let z = 0.0193339 * r + 0.1191920 * g + 0.9503041 * b;
+
+    let fx = lab_f(x / WHITE[0]);
+    let fy = lab_f(y / WHITE[1]);
+    let fz = lab_f(z / WHITE[2]);
+
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+// Lab -> XYZ -> linear -> sRGB, out of gamut colors get clamped
+pub(crate) fn lab_to_srgb(lab: [f64; 3]) -> [u8; 3] {
+    let fy = (lab[0] + 16.0) / 116.0;
+    let fx = fy + lab[1] / 500.0;
+    let fz = fy - lab[2] / 200.0;
+
+    let x = lab_f_inv(fx) * WHITE[0];
+    let y = lab_f_inv(fy) * WHITE[1];
+    let z = lab_f_inv(fz) * WHITE[2];
+
+    let r = 3.2404542 * x - 1.5371385 * y - 0.4985314 * z;
+    let g = -0.9692660 * x + 1.8760108 * y + 0.0415560 * z;
+    let b = 0.0556434 * x - 0.2040259 * y + 1.0572252 * z;
+
+    [r, g, b].map(|c| (linear_to_srgb(c.clamp(0.0, 1.0)) * 255.0).round() as u8)
+}
+
+const EPSILON: f64 = 216.0 / 24389.0;
+const KAPPA: f64 = 24389.0 / 27.0;
+
+fn lab_f(t: f64) -> f64 {
+    if t > EPSILON {
+        t.cbrt()
+    } else {
+        (KAPPA * t + 16.0) / 116.0
+    }
+}
+
+fn lab_f_inv(f: f64) -> f64 {
+    let cubed = f * f * f;
+    if cubed > EPSILON {
+        cubed
+    } else {
+        (116.0 * f - 16.0) / KAPPA
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: [f64; 3], expected: [f64; 3], tolerance: f64) {
+        for c in 0..3 {
+            assert!((actual[c] - expected[c]).abs() < tolerance, "{:?} vs {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn white_is_l100_neutral() {
+        assert_close(srgb_to_lab([255, 255, 255]), [100.0, 0.0, 0.0], 0.01);
+    }
+
+    #[test]
+    fn red_matches_reference() {
+        assert_close(srgb_to_lab([255, 0, 0]), [53.24, 80.09, 67.20], 0.01);
+    }
+
+    #[test]
+    fn round_trips_through_lab() {
+        for rgb in [[0, 0, 0], [255, 0, 0], [12, 200, 77], [128, 128, 128]] {
+            assert_eq!(lab_to_srgb(srgb_to_lab(rgb)), rgb);
+        }
+    }
+}
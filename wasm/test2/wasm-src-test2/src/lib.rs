@@ -1,168 +1,454 @@
 use wasm_bindgen::prelude::*;
 use web_sys::ImageData;
 
+use wasm_src_common::cancel::{self, CancellationToken};
+use wasm_src_common::error::{self, error, ErrorCode};
+use wasm_src_common::kmeans::{
+    centroids_converged, deterministic_sample, euclidean_distance, find_nearest_centroid,
+    initialize_centroids_deterministic, refine_centroids, remap_chunk, remap_into, rgb_pixels, train_centroids,
+    train_centroids_cancellable, train_centroids_with,
+};
+use wasm_src_common::nearest;
+
+mod alpha;
+mod cvd;
+mod codec;
+mod cooperative;
+mod integer;
+mod lab;
+mod linear;
+mod recolor;
+mod slic;
+mod suggest;
+mod trace;
+
 // Color quantization using k-means clustering
 // went with deterministic init after random gave inconsistent results across runs
 #[wasm_bindgen]
 pub fn quantize(image_data: &ImageData, k: usize) -> Result<ImageData, JsValue> {
+    quantize_checked(image_data, k, None)
+}
+
+// `quantize` that gives up with a CANCELLED error once `cancel` is tripped, see
+// cancel.rs. a separate export because wasm-bindgen can't take an optional
+// reference to a class, and taking the token by value would free the JS handle
+#[wasm_bindgen]
+pub fn quantize_cancellable(image_data: &ImageData, k: usize, cancel: &CancellationToken) -> Result<ImageData, JsValue> {
+    quantize_checked(image_data, k, Some(cancel))
+}
+
+fn quantize_checked(image_data: &ImageData, k: usize, cancel: Option<&CancellationToken>) -> Result<ImageData, JsValue> {
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+
     let width = image_data.width() as usize;
-    let height = image_data.height() as usize;
     let data = image_data.data();
-    
-    // grab all RGB values (skip alpha channel)
-    let mut pixels: Vec<[f64; 3]> = Vec::with_capacity(width * height);
-    for i in (0..data.len()).step_by(4) {
-        pixels.push([
-            data[i] as f64,
-            data[i + 1] as f64,
-            data[i + 2] as f64,
-        ]);
-    }
-    
-    // training on full image is too slow. sampling 1k pixels instead
-    let sample_size = 1000.min(pixels.len());
-    let sampled_pixels = deterministic_sample(&pixels, sample_size);
-    
-    let mut centroids = initialize_centroids_deterministic(&sampled_pixels, k);
-    
-    let max_iterations = 20; // usually converges way before this
-    for _ in 0..max_iterations {
-        let mut clusters: Vec<Vec<[f64; 3]>> = vec![Vec::new(); k];
-        
-        for pixel in &sampled_pixels {
-            let nearest = find_nearest_centroid(pixel, &centroids);
-            clusters[nearest].push(*pixel);
-        }
-        
-        let new_centroids: Vec<[f64; 3]> = clusters
-            .iter()
-            .enumerate()
-            .map(|(i, cluster)| {
-                if cluster.is_empty() {
-                    centroids[i]
-                } else {
-                    calculate_mean(cluster)
-                }
-            })
-            .collect();
-        
-        if centroids_converged(&centroids, &new_centroids, 1.0) {
-            break;
-        }
-        centroids = new_centroids;
-    }
-    
-    // mapping all pixels to nearest centroid color
+
+    let pixels = rgb_pixels(&data);
+    let centroids = train_centroids_cancellable(&pixels, k, cancel);
+    cancel::check(cancel)?;
+
     let mut output = vec![0u8; data.len()];
-    for (i, pixel) in pixels.iter().enumerate() {
-        let nearest = find_nearest_centroid(pixel, &centroids);
-        let [r, g, b] = centroids[nearest];
-        
-        output[i * 4] = r.round() as u8;
-        output[i * 4 + 1] = g.round() as u8;
-        output[i * 4 + 2] = b.round() as u8;
-        output[i * 4 + 3] = data[i * 4 + 3];
-    }
-    
+    // with a token, a band of rows at a time so it gets looked at in between
+    let band = match cancel {
+        Some(_) => (cancel::CHECK_ROWS * width).max(1),
+        None => pixels.len().max(1),
+    };
+    for ((output, data), pixels) in output
+        .chunks_mut(band * 4)
+        .zip(data.chunks(band * 4))
+        .zip(pixels.chunks(band))
+    {
+        cancel::check(cancel)?;
+        remap_into(data, pixels, &centroids, output);
+    }
+
     ImageData::new_with_u8_clamped_array(
         wasm_bindgen::Clamped(&output),
         width as u32,
     )
 }
 
-// picks evenly spaced pixels for sampling
-fn deterministic_sample(pixels: &[[f64; 3]], sample_size: usize) -> Vec<[f64; 3]> {
-    let mut sampled = Vec::with_capacity(sample_size);
-    let step = pixels.len() as f64 / sample_size as f64;
-    
-    for i in 0..sample_size {
-        let idx = (i as f64 * step).floor() as usize;
-        sampled.push(pixels[idx]);
-    }
-    
-    sampled
-}
-
-// spreads initial centroids out for faster convergence 
-fn initialize_centroids_deterministic(pixels: &[[f64; 3]], k: usize) -> Vec<[f64; 3]> {
-    if pixels.is_empty() {
-        return Vec::new();
-    }
-    
-    let mut centroids = Vec::with_capacity(k);
-    
-    // start with pixel at 1/4 mark (stable middle-ish color)
-    centroids.push(pixels[pixels.len() / 4]);
-    
-    // pick remaining centroids far apart from each other
-    for _ in 1..k {
-        let mut max_min_dist = -1.0;
-        let mut best_pixel_idx = 0;
-        
-        // don't check every single pixel. too slow        
-        let sample_rate = 1.max(pixels.len() / 1000);
-        
-        for i in (0..pixels.len()).step_by(sample_rate) {
-            let pixel = &pixels[i];
-            
-            // find closest existing centroid
-            let mut min_dist = f64::INFINITY;
-            for centroid in &centroids {
-                let dist = euclidean_distance(pixel, centroid);
-                if dist < min_dist {
-                    min_dist = dist;
-                }
-            }
-            
-            // track furthest pixel
-            if min_dist > max_min_dist {
-                max_min_dist = min_dist;
-                best_pixel_idx = i;
+
+// same as `quantize`, but calls `progress(iteration, delta)` after every k-means
+// iteration (iteration counts from 1, delta is the furthest any centroid moved)
+// so the UI can show something on big images. errors thrown by the callback are
+// ignored, a broken progress bar shouldn't kill the quantization
+#[wasm_bindgen]
+pub fn quantize_with_progress(
+    image_data: &ImageData,
+    k: usize,
+    progress: &js_sys::Function,
+) -> Result<ImageData, JsValue> {
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let data = image_data.data();
+
+    let pixels = rgb_pixels(&data);
+    let centroids = train_centroids_with(&pixels, k, |iteration, delta, _| {
+        let _ = progress.call2(
+            &JsValue::NULL,
+            &JsValue::from(iteration as u32),
+            &JsValue::from(delta),
+        );
+    });
+
+    let mut output = vec![0u8; data.len()];
+    remap_into(&data, &pixels, &centroids, &mut output);
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&output), width, height)
+}
+
+// quantized image plus the palette that produced it
+#[wasm_bindgen]
+pub struct QuantizedImage {
+    image: ImageData,
+    palette: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl QuantizedImage {
+    #[wasm_bindgen(getter)]
+    pub fn image(&self) -> ImageData {
+        self.image.clone()
+    }
+
+    // centroids as rgb triples, feed these to `quantize_warm`
+    #[wasm_bindgen(getter)]
+    pub fn palette(&self) -> Vec<u8> {
+        self.palette.clone()
+    }
+}
+
+// `quantize` that also hands back the centroids
+#[wasm_bindgen]
+pub fn quantize_with_palette(image_data: &ImageData, k: usize) -> Result<QuantizedImage, JsValue> {
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+
+    let pixels = rgb_pixels(&image_data.data());
+    let centroids = train_centroids(&pixels, k);
+    quantized_image(image_data, &pixels, &centroids)
+}
+
+// k-means that starts from `initial_centroids` (rgb triples, their count is k)
+// instead of the deterministic init. when a k slider is nudged, seeding with the
+// previous palette converges in a couple of iterations instead of a full run
+#[wasm_bindgen]
+pub fn quantize_warm(image_data: &ImageData, initial_centroids: &[u8]) -> Result<QuantizedImage, JsValue> {
+    if initial_centroids.is_empty() || !initial_centroids.len().is_multiple_of(3) {
+        return Err(error(ErrorCode::InvalidLength, "initial_centroids must be a non-empty list of rgb triples"));
+    }
+
+    let pixels = rgb_pixels(&image_data.data());
+    let centroids: Vec<[f64; 3]> = initial_centroids
+        .chunks_exact(3)
+        .map(|c| [c[0] as f64, c[1] as f64, c[2] as f64])
+        .collect();
+    let sampled_pixels = deterministic_sample(&pixels, 1000.min(pixels.len()));
+    let centroids = refine_centroids(&sampled_pixels, centroids, |_, _, _| {});
+
+    quantized_image(image_data, &pixels, &centroids)
+}
+
+fn quantized_image(
+    image_data: &ImageData,
+    pixels: &[[f64; 3]],
+    centroids: &[[f64; 3]],
+) -> Result<QuantizedImage, JsValue> {
+    let data = image_data.data();
+    let mut output = vec![0u8; data.len()];
+    remap_into(&data, pixels, centroids, &mut output);
+
+    Ok(QuantizedImage {
+        image: ImageData::new_with_u8_clamped_array_and_sh(
+            wasm_bindgen::Clamped(&output),
+            image_data.width(),
+            image_data.height(),
+        )?,
+        palette: centroids.iter().flat_map(|c| c.map(|v| v.round() as u8)).collect(),
+    })
+}
+
+// mini-batch k-means (Sculley 2010): every iteration draws a fresh batch of
+// `batch_size` pixels and nudges each centroid towards its pixels with a
+// learning rate of 1 / (pixels it has seen so far). over many iterations it
+// sees far more of the image than the 1000 pixel sample, without keeping
+// clusters around. batches are deterministic, same input -> same output
+#[wasm_bindgen]
+pub fn quantize_minibatch(
+    image_data: &ImageData,
+    k: usize,
+    batch_size: usize,
+    iterations: usize,
+) -> Result<ImageData, JsValue> {
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+    if batch_size == 0 || iterations == 0 {
+        return Err(error(ErrorCode::InvalidParameter, "batch_size and iterations must be at least 1"));
+    }
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let data = image_data.data();
+
+    let pixels = rgb_pixels(&data);
+    let mut output = vec![0u8; data.len()];
+    if !pixels.is_empty() {
+        let centroids = train_minibatch(&pixels, k, batch_size, iterations);
+        remap_into(&data, &pixels, &centroids, &mut output);
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&output), width, height)
+}
+
+fn train_minibatch(pixels: &[[f64; 3]], k: usize, batch_size: usize, iterations: usize) -> Vec<[f64; 3]> {
+    // same starting point as the regular path
+    let sample = deterministic_sample(pixels, 1000.min(pixels.len()));
+    let mut centroids = initialize_centroids_deterministic(&sample, k);
+    let mut seen = vec![0u64; centroids.len()];
+
+    // golden ratio sequence: consecutive draws are spread over the whole image
+    // and every batch continues where the last one stopped
+    const GOLDEN: f64 = 0.618_033_988_749_894_9;
+    let mut draw = 0u64;
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut nearest = Vec::with_capacity(batch_size);
+
+    for _ in 0..iterations {
+        batch.clear();
+        for _ in 0..batch_size {
+            draw += 1;
+            let index = ((draw as f64 * GOLDEN).fract() * pixels.len() as f64) as usize;
+            batch.push(pixels[index.min(pixels.len() - 1)]);
+        }
+
+        // assign the whole batch first, then move the centroids
+        nearest.clear();
+        nearest.extend(batch.iter().map(|p| find_nearest_centroid(p, &centroids)));
+
+        for (pixel, &c) in batch.iter().zip(&nearest) {
+            seen[c] += 1;
+            let rate = 1.0 / seen[c] as f64;
+            for (value, target) in centroids[c].iter_mut().zip(pixel) {
+                *value += (target - *value) * rate;
             }
         }
-        
-        centroids.push(pixels[best_pixel_idx]);
     }
-    
+
     centroids
 }
 
-fn find_nearest_centroid(pixel: &[f64; 3], centroids: &[[f64; 3]]) -> usize {
-    let mut min_dist = f64::INFINITY;
-    let mut nearest = 0;
-    
-    for (i, centroid) in centroids.iter().enumerate() {
-        let dist = euclidean_distance(pixel, centroid);
-        if dist < min_dist {
-            min_dist = dist;
-            nearest = i;
-        }
+
+// same training and assignment as `quantize`, but each pixel becomes its
+// cluster index as gray (spread over 0..255) instead of the centroid color.
+// shows how k-means carved up the color space
+#[wasm_bindgen]
+pub fn quantize_label_map(image_data: &ImageData, k: usize) -> Result<ImageData, JsValue> {
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let data = image_data.data();
+
+    let pixels = rgb_pixels(&data);
+    let centroids = train_centroids(&pixels, k);
+    let spread = 255.0 / (k - 1).max(1) as f64;
+
+    let mut output = vec![0u8; data.len()];
+    for (i, pixel) in pixels.iter().enumerate() {
+        let label = (find_nearest_centroid(pixel, &centroids) as f64 * spread).round() as u8;
+
+        output[i * 4] = label;
+        output[i * 4 + 1] = label;
+        output[i * 4 + 2] = label;
+        output[i * 4 + 3] = 255;
     }
-    
-    nearest
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&output), width, height)
 }
 
-fn euclidean_distance(p1: &[f64; 3], p2: &[f64; 3]) -> f64 {
-    let dr = p1[0] - p2[0];
-    let dg = p1[1] - p2[1];
-    let db = p1[2] - p2[2];
-    (dr * dr + dg * dg + db * db).sqrt()
+// heatmap ramp for `quantize_error_map`, evenly spaced stops
+// blue -> cyan -> green -> yellow -> red. fixed so screenshots compare
+const HEAT_STOPS: [[u8; 3]; 5] = [[0, 0, 255], [0, 255, 255], [0, 255, 0], [255, 255, 0], [255, 0, 0]];
+
+// runs the normal `quantize`, then shows how far each pixel ended up from its
+// centroid as a false-color map (CIE76 delta E, blue = exact, red = worst).
+// red is pinned to the 99th percentile error so a few outliers don't turn
+// everything else blue. alpha is 255
+#[wasm_bindgen]
+pub fn quantize_error_map(image_data: &ImageData, k: usize) -> Result<ImageData, JsValue> {
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let data = image_data.data();
+
+    let pixels = rgb_pixels(&data);
+    let centroids = train_centroids(&pixels, k);
+    let centroid_labs: Vec<[f64; 3]> = centroids
+        .iter()
+        .map(|c| lab::srgb_to_lab(c.map(|v| v.round() as u8)))
+        .collect();
+
+    let errors: Vec<f64> = pixels
+        .iter()
+        .map(|p| {
+            let nearest = find_nearest_centroid(p, &centroids);
+            let lab = lab::srgb_to_lab(p.map(|v| v as u8));
+            euclidean_distance(&lab, &centroid_labs[nearest])
+        })
+        .collect();
+
+    let mut sorted = errors.clone();
+    sorted.sort_unstable_by(f64::total_cmp);
+    let p99 = sorted
+        .get((sorted.len() * 99 / 100).min(sorted.len().saturating_sub(1)))
+        .copied()
+        .unwrap_or(0.0);
+
+    let ramp = heat_ramp();
+    let mut output = vec![0u8; data.len()];
+    for (px, e) in output.chunks_exact_mut(4).zip(errors) {
+        let t = if p99 > 0.0 { (e / p99).min(1.0) } else { 0.0 };
+        px[..3].copy_from_slice(&ramp[(t * 255.0).round() as usize]);
+        px[3] = 255;
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&output), width, height)
 }
 
-fn calculate_mean(cluster: &[[f64; 3]]) -> [f64; 3] {
-    let len = cluster.len() as f64;
-    let sum = cluster.iter().fold([0.0, 0.0, 0.0], |acc, pixel| {
-        [acc[0] + pixel[0], acc[1] + pixel[1], acc[2] + pixel[2]]
-    });
-    
-    [sum[0] / len, sum[1] / len, sum[2] / len]
+// HEAT_STOPS interpolated out to 256 entries
+fn heat_ramp() -> [[u8; 3]; 256] {
+    let segments = (HEAT_STOPS.len() - 1) as f64;
+    let mut ramp = [[0u8; 3]; 256];
+    for (i, color) in ramp.iter_mut().enumerate() {
+        let position = i as f64 / 255.0 * segments;
+        let segment = (position as usize).min(HEAT_STOPS.len() - 2);
+        let t = position - segment as f64;
+        let (a, b) = (HEAT_STOPS[segment], HEAT_STOPS[segment + 1]);
+        *color = [0, 1, 2].map(|c| (a[c] as f64 + (b[c] as f64 - a[c] as f64) * t).round() as u8);
+    }
+    ramp
 }
 
-fn centroids_converged(old: &[[f64; 3]], new: &[[f64; 3]], threshold: f64) -> bool {
-    for (old_c, new_c) in old.iter().zip(new.iter()) {
-        if euclidean_distance(old_c, new_c) > threshold {
-            return false;
+// number of distinct rgb colors (alpha ignored), handy for picking k and for
+// checking that quantize actually cut the palette down.
+// one bit per possible 24-bit color, 2 MB no matter how big the image is
+#[wasm_bindgen]
+pub fn unique_colors(image_data: &ImageData) -> u32 {
+    count_unique_colors(&image_data.data())
+}
+
+fn count_unique_colors(data: &[u8]) -> u32 {
+    let mut seen = vec![0u64; (1 << 24) / 64];
+    let mut count = 0;
+
+    for px in data.chunks_exact(4) {
+        let color = (px[0] as usize) << 16 | (px[1] as usize) << 8 | px[2] as usize;
+        let (word, bit) = (color / 64, 1u64 << (color % 64));
+        if seen[word] & bit == 0 {
+            seen[word] |= bit;
+            count += 1;
         }
     }
-    true
-}
\ No newline at end of file
+
+    count
+}
+
+// single representative rgb triple for theming, e.g. a page background:
+//   mode 0 = mean of every pixel (what quantize with k = 1 converges to)
+//   mode 1 = most common color, picked from a coarse 16x16x16 histogram and
+//            returned as the average of the pixels in the winning bucket
+// the mean of a photo is often a muddy gray, the histogram mode picks an
+// actual color from it. fully transparent pixels are skipped, an image with
+// nothing visible gives black
+#[wasm_bindgen]
+pub fn dominant_color(image_data: &ImageData, mode: u8) -> Result<Vec<u8>, JsValue> {
+    let data = image_data.data();
+    let visible = data.chunks_exact(4).filter(|px| px[3] != 0);
+
+    // rgb sums plus a count
+    let total = match mode {
+        0 => visible.fold([0u64; 4], |acc, px| {
+            [acc[0] + px[0] as u64, acc[1] + px[1] as u64, acc[2] + px[2] as u64, acc[3] + 1]
+        }),
+        1 => {
+            let mut buckets = vec![[0u64; 4]; 16 * 16 * 16];
+            for px in visible {
+                // top 4 bits of each channel
+                let index = (px[0] as usize >> 4) << 8 | (px[1] as usize >> 4) << 4 | px[2] as usize >> 4;
+                let bucket = &mut buckets[index];
+                for c in 0..3 {
+                    bucket[c] += px[c] as u64;
+                }
+                bucket[3] += 1;
+            }
+            // first bucket wins ties so the answer doesn't depend on anything but the pixels
+            buckets.into_iter().fold([0u64; 4], |best, b| if b[3] > best[3] { b } else { best })
+        }
+        _ => return Err(error(ErrorCode::InvalidMode, "unknown dominant_color mode, expected 0 or 1")),
+    };
+
+    let n = total[3].max(1);
+    Ok(total[..3].iter().map(|&s| ((s + n / 2) / n) as u8).collect())
+}
+
+// the `n` main colors of the image as flat rgb (exactly n * 3 bytes), most
+// coverage first, for a "theme from image" swatch row. same k-means as
+// `quantize` with k = n, but coverage is counted on the 1000 pixel training
+// sample and nothing is remapped, so it costs about as much as training alone.
+// an empty image gives n black swatches
+#[wasm_bindgen]
+pub fn palette_extract(image_data: &ImageData, n: usize) -> Result<Vec<u8>, JsValue> {
+    if n == 0 {
+        return Err(error(ErrorCode::InvalidK, "n must be at least 1"));
+    }
+
+    let pixels = rgb_pixels(&image_data.data());
+    let sample = deterministic_sample(&pixels, 1000.min(pixels.len()));
+    let initial = initialize_centroids_deterministic(&sample, n);
+    let centroids = refine_centroids(&sample, initial, |_, _, _| {});
+
+    let mut coverage = vec![0usize; centroids.len()];
+    for pixel in &sample {
+        coverage[find_nearest_centroid(pixel, &centroids)] += 1;
+    }
+    // stable, equal coverage keeps the training order
+    let mut order: Vec<usize> = (0..centroids.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(coverage[i]));
+
+    let mut palette: Vec<u8> = order
+        .iter()
+        .flat_map(|&i| centroids[i].map(|v| v.round() as u8))
+        .collect();
+    palette.resize(n * 3, 0);
+    Ok(palette)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_colors_counts_a_two_color_image() {
+        // alpha differs but the rgb is the same, so that's still 2
+        let data = [
+            255, 0, 0, 255, 0, 0, 255, 255,
+            255, 0, 0, 40, 0, 0, 255, 255,
+        ];
+        assert_eq!(count_unique_colors(&data), 2);
+        assert_eq!(count_unique_colors(&[]), 0);
+    }
+}
@@ -0,0 +1,45 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::linear::{decode_lut, encode};
+
+use crate::error::{error, ErrorCode};
+use crate::{find_nearest_centroid, quantize, train_centroids};
+
+// `quantize` with the k-means done in linear light (scaled to 0..255, so the
+// convergence threshold means the same). cluster means are then physically
+// right, dark clusters no longer get pulled darker than their pixels.
+// linear = false is plain `quantize`
+#[wasm_bindgen]
+pub fn quantize_opts(image_data: &ImageData, k: usize, linear: bool) -> Result<ImageData, JsValue> {
+    if !linear {
+        return quantize(image_data, k);
+    }
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let data = image_data.data();
+
+    let lut = decode_lut().map(|v| v * 255.0);
+    let pixels: Vec<[f64; 3]> = data
+        .chunks_exact(4)
+        .map(|px| [lut[px[0] as usize], lut[px[1] as usize], lut[px[2] as usize]])
+        .collect();
+    let centroids = train_centroids(&pixels, k);
+    let palette: Vec<[u8; 3]> = centroids.iter().map(|c| c.map(|v| encode(v / 255.0))).collect();
+
+    // plain scan, NearestCentroid's cache wants whole numbers
+    let mut output = vec![0u8; data.len()];
+    for (i, pixel) in pixels.iter().enumerate() {
+        let [r, g, b] = palette[find_nearest_centroid(pixel, &centroids)];
+
+        output[i * 4] = r;
+        output[i * 4 + 1] = g;
+        output[i * 4 + 2] = b;
+        output[i * 4 + 3] = data[i * 4 + 3];
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&output), width, height)
+}
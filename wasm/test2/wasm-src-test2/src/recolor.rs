@@ -0,0 +1,72 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+
+use crate::error::{error, ErrorCode};
+use crate::euclidean_distance;
+use crate::lab::{lab_to_srgb, srgb_to_lab};
+
+// swaps one color for another, e.g. recoloring a logo. tolerance is a CIE76
+// delta E from `from_rgb` (2-3 is barely visible, ~10 catches a flat logo with
+// antialiasing, 30+ grabs whole hue families).
+// pixels within half the tolerance are fully replaced, then the effect fades
+// out smoothly until it reaches zero at the tolerance, so there's no hard halo.
+// with preserve_luminance each pixel keeps its own lightness and only the
+// chroma is shifted, otherwise matching pixels become a flat `to_rgb`
+#[wasm_bindgen]
+pub fn replace_color(
+    image_data: &ImageData,
+    from_rgb: &[u8],
+    to_rgb: &[u8],
+    tolerance: f32,
+    preserve_luminance: bool,
+) -> Result<ImageData, JsValue> {
+    if from_rgb.len() < 3 || to_rgb.len() < 3 {
+        return Err(error(ErrorCode::InvalidLength, "from_rgb and to_rgb need r, g, b"));
+    }
+    if !(tolerance.is_finite() && tolerance >= 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "tolerance must be a non-negative number"));
+    }
+
+    let width = image_data.width();
+    let height = image_data.height();
+    let mut data = image_data.data().0;
+
+    let from = srgb_to_lab([from_rgb[0], from_rgb[1], from_rgb[2]]);
+    let to = srgb_to_lab([to_rgb[0], to_rgb[1], to_rgb[2]]);
+    let tolerance = tolerance as f64;
+
+    for px in data.chunks_exact_mut(4) {
+        let lab = srgb_to_lab([px[0], px[1], px[2]]);
+        let weight = match_weight(euclidean_distance(&lab, &from), tolerance);
+        if weight == 0.0 {
+            continue; // untouched pixels keep their exact bytes
+        }
+
+        let target = if preserve_luminance {
+            [lab[0], lab[1] + to[1] - from[1], lab[2] + to[2] - from[2]]
+        } else {
+            to
+        };
+        let blended = [0, 1, 2].map(|c| lab[c] + (target[c] - lab[c]) * weight);
+
+        let [r, g, b] = lab_to_srgb(blended);
+        px[0] = r;
+        px[1] = g;
+        px[2] = b;
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&data), width, height)
+}
+
+// 1 inside tolerance / 2, smoothstep down to 0 at the tolerance
+fn match_weight(distance: f64, tolerance: f64) -> f64 {
+    let inner = tolerance * 0.5;
+    if distance <= inner {
+        1.0
+    } else if distance >= tolerance {
+        0.0
+    } else {
+        let t = (tolerance - distance) / (tolerance - inner);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
@@ -0,0 +1,293 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+
+use crate::error::{error, ErrorCode};
+use crate::lab::srgb_to_lab;
+use crate::{centroids_converged, euclidean_distance};
+
+#[wasm_bindgen]
+pub struct SlicResult {
+    labels: Vec<u32>,
+    count: u32,
+    image: ImageData,
+}
+
+#[wasm_bindgen]
+impl SlicResult {
+    // superpixel index per pixel, row-major, 0..count
+    #[wasm_bindgen(getter)]
+    pub fn labels(&self) -> Vec<u32> {
+        self.labels.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    // the original image with superpixel boundaries drawn in red
+    #[wasm_bindgen(getter)]
+    pub fn image(&self) -> ImageData {
+        self.image.clone()
+    }
+}
+
+// SLIC superpixels (Achanta et al. 2012): k-means on (L, a, b, x, y) where
+// every center only looks at a 2S x 2S window around itself, S being the grid
+// spacing for `num_superpixels` cells. compactness trades color against
+// distance, ~10 is typical, higher gives rounder and more regular cells
+#[wasm_bindgen]
+pub fn slic(image_data: &ImageData, num_superpixels: u32, compactness: f32) -> Result<SlicResult, JsValue> {
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let mut data = image_data.data().0;
+    let (labels, count) = superpixel_labels(&data, width, height, num_superpixels, compactness)?;
+
+    // boundary wherever the right or bottom neighbour is in another cell
+    for y in 0..height {
+        for x in 0..width {
+            let i = y * width + x;
+            let right = x + 1 < width && labels[i + 1] != labels[i];
+            let below = y + 1 < height && labels[i + width] != labels[i];
+            if right || below {
+                data[i * 4] = 255;
+                data[i * 4 + 1] = 0;
+                data[i * 4 + 2] = 0;
+            }
+        }
+    }
+
+    Ok(SlicResult {
+        labels,
+        count,
+        image: ImageData::new_with_u8_clamped_array_and_sh(
+            wasm_bindgen::Clamped(&data),
+            width as u32,
+            height as u32,
+        )?,
+    })
+}
+
+// the same superpixels as `slic`, but every pixel is painted with the mean
+// color of its cell, a flat "stained glass" look. alpha is kept. use `slic`
+// when the label map is needed too
+#[wasm_bindgen]
+pub fn slic_superpixels(image_data: &ImageData, num_segments: u32, compactness: f32) -> Result<ImageData, JsValue> {
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let mut data = image_data.data().0;
+    let (labels, count) = superpixel_labels(&data, width, height, num_segments, compactness)?;
+
+    let mut sums = vec![[0u64; 4]; count as usize];
+    for (px, &label) in data.chunks_exact(4).zip(&labels) {
+        let sum = &mut sums[label as usize];
+        for c in 0..3 {
+            sum[c] += px[c] as u64;
+        }
+        sum[3] += 1;
+    }
+    let means: Vec<[u8; 3]> = sums
+        .iter()
+        .map(|s| {
+            let n = s[3].max(1);
+            [0, 1, 2].map(|c| ((s[c] + n / 2) / n) as u8)
+        })
+        .collect();
+
+    for (px, &label) in data.chunks_exact_mut(4).zip(&labels) {
+        px[..3].copy_from_slice(&means[label as usize]);
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        width as u32,
+        height as u32,
+    )
+}
+
+// validated SLIC + connectivity pass shared by both exports, (labels, count)
+fn superpixel_labels(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    num_superpixels: u32,
+    compactness: f32,
+) -> Result<(Vec<u32>, u32), JsValue> {
+    if num_superpixels == 0 {
+        return Err(error(ErrorCode::InvalidParameter, "num_superpixels must be at least 1"));
+    }
+    if !(compactness.is_finite() && compactness > 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "compactness must be a positive number"));
+    }
+
+    let labs: Vec<[f64; 3]> = data
+        .chunks_exact(4)
+        .map(|px| srgb_to_lab([px[0], px[1], px[2]]))
+        .collect();
+    if labs.is_empty() {
+        return Ok((Vec::new(), 0));
+    }
+    let step = ((labs.len() as f64 / num_superpixels as f64).sqrt()).max(1.0);
+    let raw = cluster(&labs, width, height, step, compactness as f64);
+    // cells smaller than a quarter of the nominal size get absorbed
+    Ok(enforce_connectivity(&raw, width, height, (step * step / 4.0) as usize))
+}
+
+// raw SLIC labels, cells may still be split into islands
+fn cluster(labs: &[[f64; 3]], width: usize, height: usize, step: f64, compactness: f64) -> Vec<usize> {
+    // centers on a regular grid, nudged to the flattest spot in their 3x3 so
+    // they don't start on an edge
+    let mut colors: Vec<[f64; 3]> = Vec::new();
+    let mut positions: Vec<[f64; 3]> = Vec::new(); // x, y, 0 so the 3d helpers apply
+    let mut gy = step / 2.0;
+    while gy < height as f64 {
+        let mut gx = step / 2.0;
+        while gx < width as f64 {
+            let (x, y) = lowest_gradient(labs, width, height, gx as usize, gy as usize);
+            colors.push(labs[y * width + x]);
+            positions.push([x as f64, y as f64, 0.0]);
+            gx += step;
+        }
+        gy += step;
+    }
+
+    let weight = compactness / step;
+    let window = step.ceil() as isize;
+    let mut labels = vec![0usize; labs.len()];
+    let mut distances = vec![f64::INFINITY; labs.len()];
+
+    // the paper finds 10 iterations enough for most images
+    for _ in 0..10 {
+        distances.fill(f64::INFINITY);
+
+        // assignment: each center only claims pixels within its window
+        for (c, (color, position)) in colors.iter().zip(&positions).enumerate() {
+            let (cx, cy) = (position[0] as isize, position[1] as isize);
+            let y0 = (cy - window).max(0) as usize;
+            let y1 = ((cy + window) as usize).min(height - 1);
+            let x0 = (cx - window).max(0) as usize;
+            let x1 = ((cx + window) as usize).min(width - 1);
+
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    let i = y * width + x;
+                    let color_distance = euclidean_distance(&labs[i], color);
+                    let spatial = euclidean_distance(&[x as f64, y as f64, 0.0], position) * weight;
+                    let d = color_distance * color_distance + spatial * spatial;
+                    if d < distances[i] {
+                        distances[i] = d;
+                        labels[i] = c;
+                    }
+                }
+            }
+        }
+
+        // update: centers move to the mean of what they claimed
+        let mut sums = vec![[0f64; 5]; colors.len()];
+        let mut counts = vec![0usize; colors.len()];
+        for (i, &label) in labels.iter().enumerate() {
+            let lab = labs[i];
+            let sum = &mut sums[label];
+            sum[0] += lab[0];
+            sum[1] += lab[1];
+            sum[2] += lab[2];
+            sum[3] += (i % width) as f64;
+            sum[4] += (i / width) as f64;
+            counts[label] += 1;
+        }
+
+        let mut new_colors = colors.clone();
+        let mut new_positions = positions.clone();
+        for (c, (sum, &n)) in sums.iter().zip(&counts).enumerate() {
+            if n > 0 {
+                let n = n as f64;
+                new_colors[c] = [sum[0] / n, sum[1] / n, sum[2] / n];
+                new_positions[c] = [sum[3] / n, sum[4] / n, 0.0];
+            }
+        }
+
+        let settled = centroids_converged(&colors, &new_colors, 0.5)
+            && centroids_converged(&positions, &new_positions, 0.5);
+        colors = new_colors;
+        positions = new_positions;
+        if settled {
+            break;
+        }
+    }
+
+    labels
+}
+
+fn lowest_gradient(labs: &[[f64; 3]], width: usize, height: usize, x: usize, y: usize) -> (usize, usize) {
+    let at = |x: usize, y: usize| labs[y.min(height - 1) * width + x.min(width - 1)];
+    let mut best = (x.min(width - 1), y.min(height - 1));
+    let mut best_gradient = f64::INFINITY;
+
+    for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+        for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+            let dx = euclidean_distance(&at(nx + 1, ny), &at(nx.saturating_sub(1), ny));
+            let dy = euclidean_distance(&at(nx, ny + 1), &at(nx, ny.saturating_sub(1)));
+            let gradient = dx * dx + dy * dy;
+            if gradient < best_gradient {
+                best_gradient = gradient;
+                best = (nx, ny);
+            }
+        }
+    }
+    best
+}
+
+// relabels every 4-connected piece separately, so no superpixel is split into
+// islands. pieces below `min_size` join the cell they touch instead.
+// returns dense labels and their count
+fn enforce_connectivity(raw: &[usize], width: usize, height: usize, min_size: usize) -> (Vec<u32>, u32) {
+    const UNSET: u32 = u32::MAX;
+    let mut labels = vec![UNSET; raw.len()];
+    let mut next = 0u32;
+    let mut piece = Vec::new();
+    let mut stack = Vec::new();
+
+    let neighbours = |i: usize| {
+        let (x, y) = (i % width, i / width);
+        [
+            (x > 0).then(|| i - 1),
+            (x + 1 < width).then(|| i + 1),
+            (y > 0).then(|| i - width),
+            (y + 1 < height).then(|| i + width),
+        ]
+        .into_iter()
+        .flatten()
+    };
+
+    for start in 0..raw.len() {
+        if labels[start] != UNSET {
+            continue;
+        }
+        // scan order means an already labeled neighbour is left or above
+        let adjacent = neighbours(start).map(|n| labels[n]).find(|&l| l != UNSET);
+
+        piece.clear();
+        labels[start] = next;
+        stack.push(start);
+        while let Some(i) = stack.pop() {
+            piece.push(i);
+            for n in neighbours(i) {
+                if labels[n] == UNSET && raw[n] == raw[start] {
+                    labels[n] = next;
+                    stack.push(n);
+                }
+            }
+        }
+
+        match adjacent {
+            Some(label) if piece.len() < min_size => {
+                for &i in &piece {
+                    labels[i] = label;
+                }
+            }
+            _ => next += 1,
+        }
+    }
+
+    (labels, next)
+}
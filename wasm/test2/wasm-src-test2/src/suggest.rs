@@ -0,0 +1,163 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+
+use crate::error::{error, ErrorCode};
+use crate::{
+    deterministic_sample, euclidean_distance, find_nearest_centroid, initialize_centroids_deterministic,
+    refine_centroids, rgb_pixels,
+};
+
+// silhouette is O(n^2), so it only looks at every 4th pixel of the sample
+const SILHOUETTE_STRIDE: usize = 4;
+
+#[wasm_bindgen]
+pub struct KAnalysis {
+    suggested_k: usize,
+    k_min: usize,
+    wcss: Vec<f64>,
+    silhouette: Vec<f64>,
+}
+
+#[wasm_bindgen]
+impl KAnalysis {
+    // the elbow of the wcss curve
+    #[wasm_bindgen(getter)]
+    pub fn suggested_k(&self) -> usize {
+        self.suggested_k
+    }
+
+    // entry i of the curves belongs to k = k_min + i
+    #[wasm_bindgen(getter)]
+    pub fn k_min(&self) -> usize {
+        self.k_min
+    }
+
+    // within-cluster sum of squared rgb distances over the sample, one per k.
+    // always goes down as k grows, the question is where it stops paying off
+    #[wasm_bindgen(getter)]
+    pub fn wcss(&self) -> Vec<f64> {
+        self.wcss.clone()
+    }
+
+    // mean silhouette (-1..1, higher = better separated clusters) per k, NaN
+    // for k = 1 where it isn't defined
+    #[wasm_bindgen(getter)]
+    pub fn silhouette(&self) -> Vec<f64> {
+        self.silhouette.clone()
+    }
+}
+
+// runs the sampled k-means of `quantize` for every k in k_min..=k_max on one
+// shared 1000 pixel sample, so the curves are cheap and don't jitter from
+// resampling. the suggestion is the elbow: the point furthest below the
+// straight line from the first to the last point of the normalized curve
+#[wasm_bindgen]
+pub fn suggest_k(image_data: &ImageData, k_min: usize, k_max: usize) -> Result<KAnalysis, JsValue> {
+    if k_min == 0 {
+        return Err(error(ErrorCode::InvalidK, "k_min must be at least 1"));
+    }
+    if k_max < k_min {
+        return Err(error(ErrorCode::InvalidK, "k_max must not be less than k_min"));
+    }
+
+    let pixels = rgb_pixels(&image_data.data());
+    let sample = deterministic_sample(&pixels, 1000.min(pixels.len()));
+
+    let mut wcss = Vec::with_capacity(k_max - k_min + 1);
+    let mut silhouette = Vec::with_capacity(k_max - k_min + 1);
+    for k in k_min..=k_max {
+        let initial = initialize_centroids_deterministic(&sample, k);
+        let centroids = refine_centroids(&sample, initial, |_, _, _| {});
+        let labels: Vec<usize> = sample.iter().map(|p| find_nearest_centroid(p, &centroids)).collect();
+
+        wcss.push(
+            sample
+                .iter()
+                .zip(&labels)
+                .map(|(p, &l)| euclidean_distance(p, &centroids[l]).powi(2))
+                .sum(),
+        );
+        silhouette.push(mean_silhouette(&sample, &labels, k));
+    }
+
+    Ok(KAnalysis {
+        suggested_k: k_min + elbow(&wcss),
+        k_min,
+        wcss,
+        silhouette,
+    })
+}
+
+fn elbow(curve: &[f64]) -> usize {
+    let n = curve.len();
+    if n < 3 {
+        return 0;
+    }
+    let (first, last) = (curve[0], curve[n - 1]);
+    let range = first - last;
+    if range <= 0.0 {
+        return 0;
+    }
+
+    // both axes to 0..1, then the chord runs from (0, 1) to (1, 0) and the
+    // distance below it is just 1 - x - y (up to a constant factor)
+    let mut best = (0, 0.0);
+    for (i, &v) in curve.iter().enumerate() {
+        let x = i as f64 / (n - 1) as f64;
+        let y = (v - last) / range;
+        let below = 1.0 - x - y;
+        if below > best.1 {
+            best = (i, below);
+        }
+    }
+    best.0
+}
+
+// mean of (b - a) / max(a, b) over a thinned sample, a = mean distance to the
+// own cluster, b = mean distance to the closest other cluster
+fn mean_silhouette(sample: &[[f64; 3]], labels: &[usize], k: usize) -> f64 {
+    if k < 2 {
+        return f64::NAN;
+    }
+    let points: Vec<(&[f64; 3], usize)> = sample
+        .iter()
+        .zip(labels.iter().copied())
+        .step_by(SILHOUETTE_STRIDE)
+        .collect();
+
+    let mut total = 0.0;
+    let mut counted = 0;
+    let mut sums = vec![0f64; k];
+    let mut counts = vec![0usize; k];
+    for (i, &(p, own)) in points.iter().enumerate() {
+        sums.fill(0.0);
+        counts.fill(0);
+        for (j, &(q, label)) in points.iter().enumerate() {
+            if i != j {
+                sums[label] += euclidean_distance(p, q);
+                counts[label] += 1;
+            }
+        }
+        // a lone point in its cluster scores 0 by convention
+        if counts[own] == 0 {
+            counted += 1;
+            continue;
+        }
+        let a = sums[own] / counts[own] as f64;
+        let b = (0..k)
+            .filter(|&c| c != own && counts[c] > 0)
+            .map(|c| sums[c] / counts[c] as f64)
+            .fold(f64::INFINITY, f64::min);
+        if b.is_finite() {
+            let denom = a.max(b);
+            total += if denom > 0.0 { (b - a) / denom } else { 0.0 };
+        }
+        counted += 1;
+    }
+
+    if counted == 0 {
+        f64::NAN
+    } else {
+        total / counted as f64
+    }
+}
@@ -0,0 +1,44 @@
+use js_sys::{Array, Float32Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+
+use crate::error::{error, ErrorCode};
+use crate::{deterministic_sample, initialize_centroids_deterministic, rgb_pixels, train_centroids_with};
+
+// data for a 3D view of k-means in the rgb cube, everything normalized to 0..1:
+//   points:    Float32Array of xyz (= rgb) triples, `sample_for_plot` pixels
+//              picked with the same deterministic sampling as training
+//   centroids: Array of Float32Array, one per step. the first entry is the
+//              initial guess, then one per iteration
+#[wasm_bindgen]
+pub fn quantize_trace_points(image_data: &ImageData, k: usize, sample_for_plot: usize) -> Result<JsValue, JsValue> {
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+
+    let pixels = rgb_pixels(&image_data.data());
+    let plotted = deterministic_sample(&pixels, sample_for_plot.min(pixels.len()));
+
+    let steps = Array::new();
+    if !pixels.is_empty() {
+        // training redoes this internally, it's cheap on 1000 pixels
+        let sample = deterministic_sample(&pixels, 1000.min(pixels.len()));
+        steps.push(&normalized(&initialize_centroids_deterministic(&sample, k)));
+        train_centroids_with(&pixels, k, |_, _, centroids| {
+            steps.push(&normalized(centroids));
+        });
+    }
+
+    let result = Object::new();
+    Reflect::set(&result, &"points".into(), &normalized(&plotted))?;
+    Reflect::set(&result, &"centroids".into(), &steps)?;
+    Ok(result.into())
+}
+
+fn normalized(colors: &[[f64; 3]]) -> Float32Array {
+    let flat: Vec<f32> = colors
+        .iter()
+        .flat_map(|c| c.map(|v| (v / 255.0) as f32))
+        .collect();
+    Float32Array::from(&flat[..])
+}
@@ -0,0 +1,111 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::border::BorderMode;
+
+// edge preserving smoothing: neighbours are weighted by distance (spatial_sigma,
+// in pixels) AND by how close their value is (range_sigma, in 0..255 units),
+// so flat areas blur but strong edges stay crisp. each rgb channel is filtered
+// on its own, alpha is kept.
+// cost is O(pixels * radius^2) with radius = 2 * spatial_sigma, keep
+// spatial_sigma around 1-3 for interactive use
+#[wasm_bindgen]
+pub fn bilateral_filter(image_data: &ImageData, spatial_sigma: f32, range_sigma: f32) -> Result<ImageData, JsValue> {
+    bilateral_checked(image_data, spatial_sigma, range_sigma, BorderMode::Clamp)
+}
+
+// bilateral_filter with a choice of border mode, bilateral_filter itself
+// clamps. zero padding only pulls in black where the edge is already dark,
+// the range weights keep it out everywhere else
+#[wasm_bindgen]
+pub fn bilateral_filter_border(
+    image_data: &ImageData,
+    spatial_sigma: f32,
+    range_sigma: f32,
+    border: u8,
+) -> Result<ImageData, JsValue> {
+    bilateral_checked(image_data, spatial_sigma, range_sigma, BorderMode::from_u8(border)?)
+}
+
+fn bilateral_checked(
+    image_data: &ImageData,
+    spatial_sigma: f32,
+    range_sigma: f32,
+    border: BorderMode,
+) -> Result<ImageData, JsValue> {
+    if !(spatial_sigma.is_finite() && spatial_sigma > 0.0 && range_sigma.is_finite() && range_sigma > 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "sigmas must be positive numbers"));
+    }
+
+    let data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let radius = ((spatial_sigma * 2.0).ceil() as isize).max(1);
+    let size = (radius * 2 + 1) as usize;
+
+    // spatial weights only depend on the offset, so compute them once
+    let mut spatial = vec![0f32; size * size];
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let d2 = (dx * dx + dy * dy) as f32;
+            spatial[((dy + radius) as usize) * size + (dx + radius) as usize] =
+                (-d2 / (2.0 * spatial_sigma * spatial_sigma)).exp();
+        }
+    }
+    // and range weights only on the absolute difference
+    let mut range = [0f32; 256];
+    for (d, w) in range.iter_mut().enumerate() {
+        let d = d as f32;
+        *w = (-(d * d) / (2.0 * range_sigma * range_sigma)).exp();
+    }
+
+    // y + dy is entry y + dy + radius
+    let xs = border.resolve_range(width, radius as usize);
+    let ys = border.resolve_range(height, radius as usize);
+
+    let mut output = vec![0u8; data.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let center = (y * width + x) * 4;
+            let mut sums = [0f32; 3];
+            let mut weights = [0f32; 3];
+
+            for dy in -radius..=radius {
+                let sy = ys[(y as isize + dy + radius) as usize];
+                for dx in -radius..=radius {
+                    let sx = xs[(x as isize + dx + radius) as usize];
+                    // zero padding is a black pixel, it still gets weighed
+                    let px = match (sx, sy) {
+                        (Some(sx), Some(sy)) => {
+                            let idx = (sy * width + sx) * 4;
+                            [data[idx], data[idx + 1], data[idx + 2]]
+                        }
+                        _ => [0; 3],
+                    };
+                    let ws = spatial[((dy + radius) as usize) * size + (dx + radius) as usize];
+
+                    for c in 0..3 {
+                        let diff = (px[c] as i32 - data[center + c] as i32).unsigned_abs() as usize;
+                        let w = ws * range[diff];
+                        sums[c] += px[c] as f32 * w;
+                        weights[c] += w;
+                    }
+                }
+            }
+
+            for c in 0..3 {
+                // the center pixel always has weight 1, so this never divides by 0
+                output[center + c] = (sums[c] / weights[c]).round().clamp(0.0, 255.0) as u8;
+            }
+            output[center + 3] = data[center + 3];
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
@@ -0,0 +1,203 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::{box_blur_in_place, box_radius, gaussian_blur_in_place_cancellable, EdgeOperator};
+
+// what kernel filters read past the edge of the image, passed as a u8:
+// 0 = zero (transparent black, edges darken), 1 = clamp (repeat the edge
+// pixel), 2 = reflect (mirror without repeating the edge pixel, dcb|abcd|cba,
+// no dark or smeared rim), 3 = wrap (the opposite edge, for tileable textures)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BorderMode {
+    Zero,
+    Clamp,
+    Reflect,
+    Wrap,
+}
+
+impl BorderMode {
+    pub(crate) fn from_u8(mode: u8) -> Result<BorderMode, JsValue> {
+        match mode {
+            0 => Ok(BorderMode::Zero),
+            1 => Ok(BorderMode::Clamp),
+            2 => Ok(BorderMode::Reflect),
+            3 => Ok(BorderMode::Wrap),
+            _ => Err(error(ErrorCode::InvalidMode, "unknown border mode, expected 0-3")),
+        }
+    }
+
+    // index into a line of `len` for a possibly out of range `i`, None for zero padding
+    pub(crate) fn resolve(self, i: isize, len: usize) -> Option<usize> {
+        let last = len as isize - 1;
+        if (0..=last).contains(&i) {
+            return Some(i as usize);
+        }
+        match self {
+            BorderMode::Zero => None,
+            BorderMode::Clamp => Some(i.clamp(0, last) as usize),
+            BorderMode::Reflect if last == 0 => Some(0),
+            BorderMode::Reflect => {
+                let period = 2 * last;
+                let i = i.rem_euclid(period);
+                Some((if i > last { period - i } else { i }) as usize)
+            }
+            BorderMode::Wrap => Some(i.rem_euclid(len as isize) as usize),
+        }
+    }
+
+    // `resolve` for every i in -pad..len + pad, entry j is for i = j - pad.
+    // lets inner loops index a table instead of matching on the mode per tap
+    pub(crate) fn resolve_range(self, len: usize, pad: usize) -> Vec<Option<usize>> {
+        if len == 0 {
+            return Vec::new();
+        }
+        (-(pad as isize)..(len + pad) as isize).map(|i| self.resolve(i, len)).collect()
+    }
+}
+
+// RGBA pixel at (x, y), which may be outside the image
+pub(crate) fn sample(data: &[u8], x: isize, y: isize, width: usize, height: usize, mode: BorderMode) -> [u8; 4] {
+    match (mode.resolve(x, width), mode.resolve(y, height)) {
+        (Some(x), Some(y)) => {
+            let idx = (y * width + x) * 4;
+            [data[idx], data[idx + 1], data[idx + 2], data[idx + 3]]
+        }
+        _ => [0; 4],
+    }
+}
+
+// gaussian_blur with a choice of border mode, gaussian_blur itself clamps.
+// with wrap a tileable texture stays tileable: the left edge is blurred
+// together with the right one and the top with the bottom, so the seam still
+// lines up afterwards
+#[wasm_bindgen]
+pub fn gaussian_blur_border(image_data: &ImageData, sigma: f32, border: u8) -> Result<ImageData, JsValue> {
+    if !(sigma.is_finite() && sigma > 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "sigma must be a positive number"));
+    }
+    let border = BorderMode::from_u8(border)?;
+
+    let mut data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut scratch = vec![0u8; data.len()];
+    gaussian_blur_in_place_cancellable(&mut data, &mut scratch, width, height, sigma, border, None);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        width as u32,
+        height as u32,
+    )
+}
+
+// mean_blur with a choice of border mode, mean_blur itself clamps
+#[wasm_bindgen]
+pub fn mean_blur_border(image_data: &ImageData, radius: u32, border: u8) -> Result<ImageData, JsValue> {
+    let border = BorderMode::from_u8(border)?;
+
+    let mut data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    if radius > 0 {
+        let mut scratch = vec![0u8; data.len()];
+        box_blur_in_place(&mut data, &mut scratch, width, height, box_radius(radius, width, height), border);
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        width as u32,
+        height as u32,
+    )
+}
+
+// edge_detection_operator that also covers the outermost pixels, reading past
+// the edge with `border` instead of leaving a transparent rim. the pre-blur
+// and the gradient both use it. zero mode finds an edge all around any image
+// that isn't dark at the border, reflect and clamp don't
+#[wasm_bindgen]
+pub fn edge_detection_border(image_data: &ImageData, operator: EdgeOperator, border: u8) -> Result<ImageData, JsValue> {
+    let border = BorderMode::from_u8(border)?;
+
+    let data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut blurred = vec![0u8; data.len()];
+    blur_into_border(&data, &mut blurred, width, height, border);
+
+    let mut output = vec![0u8; data.len()];
+    edges_into_border(&blurred, &mut output, width, height, 100, operator, border);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+// blur_into for every pixel, the 3x3 reads past the edge with `border`
+fn blur_into_border(data: &[u8], out: &mut [u8], width: usize, height: usize, border: BorderMode) {
+    let kernel: [[i32; 3]; 3] = [[1, 2, 1], [2, 4, 2], [1, 2, 1]];
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut acc = 0i32;
+            for (ky, row) in kernel.iter().enumerate() {
+                for (kx, weight) in row.iter().enumerate() {
+                    let px = sample(data, (x + kx) as isize - 1, (y + ky) as isize - 1, width, height, border);
+                    let gray = ((px[0] as i32 + px[1] as i32 + px[2] as i32) as f64 / 3.0).round() as i32;
+                    acc += gray * weight;
+                }
+            }
+
+            let g = (acc as f64 / 16.0).round() as u8;
+            let idx = (y * width + x) * 4;
+            out[idx] = g;
+            out[idx + 1] = g;
+            out[idx + 2] = g;
+            out[idx + 3] = 255;
+        }
+    }
+}
+
+// edges_into for every pixel, the kernels read past the edge with `border`
+fn edges_into_border(
+    input: &[u8],
+    output: &mut [u8],
+    width: usize,
+    height: usize,
+    thresh: u8,
+    operator: EdgeOperator,
+    border: BorderMode,
+) {
+    let (size, kernel_x, kernel_y) = operator.kernels();
+    // same anchoring as `gradient`: 3x3 centered, roberts at its top-left
+    let offset = if size == 3 { 1 } else { 0 };
+    let scale = operator.scale();
+
+    for y in 0..height {
+        for x in 0..width {
+            let (mut gx, mut gy) = (0i32, 0i32);
+            for ky in 0..size {
+                for kx in 0..size {
+                    let sx = (x + kx) as isize - offset;
+                    let sy = (y + ky) as isize - offset;
+                    let gray = sample(input, sx, sy, width, height, border)[0] as i32;
+                    gx += gray * kernel_x[ky * size + kx];
+                    gy += gray * kernel_y[ky * size + kx];
+                }
+            }
+
+            let magnitude = (((gx * gx + gy * gy) as f64).sqrt() * scale).round().min(255.0) as u8;
+            let edge = if magnitude > thresh { 255 } else { 0 };
+            let idx = (y * width + x) * 4;
+            output[idx] = edge;
+            output[idx + 1] = edge;
+            output[idx + 2] = edge;
+            output[idx + 3] = 255;
+        }
+    }
+}
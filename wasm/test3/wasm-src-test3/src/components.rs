@@ -0,0 +1,119 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+
+// blob analysis on a binary map (edge_detection, adaptive_threshold output...).
+// any pixel with non-zero rgb is foreground, 8-connected pixels share a label
+#[wasm_bindgen]
+pub struct ComponentLabels {
+    labels: Vec<u32>,
+    count: u32,
+}
+
+#[wasm_bindgen]
+impl ComponentLabels {
+    // one label per pixel, row-major. 0 = background, components are 1..=count
+    #[wasm_bindgen(getter)]
+    pub fn labels(&self) -> Vec<u32> {
+        self.labels.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+// classic two-pass labeling: provisional labels + union-find, then relabel
+#[wasm_bindgen]
+pub fn label_components(image_data: &ImageData) -> ComponentLabels {
+    let data = image_data.data();
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let foreground: Vec<bool> = data
+        .chunks_exact(4)
+        .map(|px| px[0] != 0 || px[1] != 0 || px[2] != 0)
+        .collect();
+
+    let (labels, count) = label_pixels(&foreground, width, height);
+    ComponentLabels { labels, count }
+}
+
+fn label_pixels(foreground: &[bool], width: usize, height: usize) -> (Vec<u32>, u32) {
+    let mut labels = vec![0u32; width * height];
+    // parent[0] is the background and never used as a component
+    let mut parent: Vec<u32> = vec![0];
+
+    for y in 0..height {
+        for x in 0..width {
+            let i = y * width + x;
+            if !foreground[i] {
+                continue;
+            }
+
+            // already visited neighbours: left, up-left, up, up-right
+            let mut neighbours = [0u32; 4];
+            if x > 0 {
+                neighbours[0] = labels[i - 1];
+            }
+            if y > 0 {
+                let above = i - width;
+                if x > 0 {
+                    neighbours[1] = labels[above - 1];
+                }
+                neighbours[2] = labels[above];
+                if x + 1 < width {
+                    neighbours[3] = labels[above + 1];
+                }
+            }
+
+            let mut label = 0;
+            for &n in neighbours.iter().filter(|&&n| n != 0) {
+                if label == 0 {
+                    label = n;
+                } else {
+                    union(&mut parent, label, n);
+                }
+            }
+            if label == 0 {
+                label = parent.len() as u32;
+                parent.push(label);
+            }
+            labels[i] = label;
+        }
+    }
+
+    // second pass: resolve to roots and renumber densely in scan order
+    let mut remap = vec![0u32; parent.len()];
+    let mut count = 0;
+    for label in labels.iter_mut().filter(|l| **l != 0) {
+        let root = find(&mut parent, *label) as usize;
+        if remap[root] == 0 {
+            count += 1;
+            remap[root] = count;
+        }
+        *label = remap[root];
+    }
+
+    (labels, count)
+}
+
+fn find(parent: &mut [u32], mut x: u32) -> u32 {
+    while parent[x as usize] != x {
+        // path halving
+        parent[x as usize] = parent[parent[x as usize] as usize];
+        x = parent[x as usize];
+    }
+    x
+}
+
+fn union(parent: &mut [u32], a: u32, b: u32) {
+    let ra = find(parent, a);
+    let rb = find(parent, b);
+    // keep the smaller label as root so roots stay stable
+    if ra < rb {
+        parent[rb as usize] = ra;
+    } else if rb < ra {
+        parent[ra as usize] = rb;
+    }
+}
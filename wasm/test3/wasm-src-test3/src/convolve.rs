@@ -0,0 +1,218 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::border::{sample, BorderMode};
+
+// classic engraved look. direction picks where the "light" comes from:
+//   0 = top-left, 1 = top-right, 2 = left, 3 = top
+// kernels sum to 0 and get a +128 bias, so flat areas turn mid gray
+#[wasm_bindgen]
+pub fn emboss(image_data: &ImageData, direction: u8) -> Result<ImageData, JsValue> {
+    emboss_checked(image_data, direction, BorderMode::Clamp)
+}
+
+// emboss with a choice of border mode, emboss itself clamps
+#[wasm_bindgen]
+pub fn emboss_border(image_data: &ImageData, direction: u8, border: u8) -> Result<ImageData, JsValue> {
+    emboss_checked(image_data, direction, BorderMode::from_u8(border)?)
+}
+
+fn emboss_checked(image_data: &ImageData, direction: u8, border: BorderMode) -> Result<ImageData, JsValue> {
+    let kernel: [f32; 9] = match direction {
+        0 => [-2.0, -1.0, 0.0, -1.0, 0.0, 1.0, 0.0, 1.0, 2.0],
+        1 => [0.0, -1.0, -2.0, 1.0, 0.0, -1.0, 2.0, 1.0, 0.0],
+        2 => [-1.0, 0.0, 1.0, -2.0, 0.0, 2.0, -1.0, 0.0, 1.0],
+        3 => [-1.0, -2.0, -1.0, 0.0, 0.0, 0.0, 1.0, 2.0, 1.0],
+        _ => return Err(error(ErrorCode::InvalidMode, "unknown emboss direction, expected 0-3")),
+    };
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let output = convolve3x3(&image_data.data(), width, height, &kernel, 1.0, 128.0, border);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+// directional streaking: every pixel becomes the rgb average of `length`
+// samples on a line through it at `angle` degrees (0 = horizontal, counter
+// clockwise with y up). samples are nearest-neighbour and clamp at the
+// borders. cost is O(pixels * length), keep length modest on video
+#[wasm_bindgen]
+pub fn motion_blur(image_data: &ImageData, length: u32, angle: f32) -> Result<ImageData, JsValue> {
+    motion_blur_checked(image_data, length, angle, BorderMode::Clamp)
+}
+
+// motion_blur with a choice of border mode. wrap keeps streaks on a tileable
+// texture seamless, zero fades the edges towards black
+#[wasm_bindgen]
+pub fn motion_blur_border(image_data: &ImageData, length: u32, angle: f32, border: u8) -> Result<ImageData, JsValue> {
+    motion_blur_checked(image_data, length, angle, BorderMode::from_u8(border)?)
+}
+
+fn motion_blur_checked(image_data: &ImageData, length: u32, angle: f32, border: BorderMode) -> Result<ImageData, JsValue> {
+    if length == 0 {
+        return Err(error(ErrorCode::InvalidParameter, "length must be at least 1"));
+    }
+    if !angle.is_finite() {
+        return Err(error(ErrorCode::InvalidParameter, "angle must be finite"));
+    }
+
+    let data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    // sample offsets are the same for every pixel, centered on it
+    let (sin, cos) = (angle as f64).to_radians().sin_cos();
+    let half = (length - 1) as f64 / 2.0;
+    let offsets: Vec<(isize, isize)> = (0..length)
+        .map(|i| {
+            let t = i as f64 - half;
+            // canvas y points down, flip it so positive angles go up
+            ((t * cos).round() as isize, (-t * sin).round() as isize)
+        })
+        .collect();
+
+    let mut output = data.clone();
+    for y in 0..height {
+        for x in 0..width {
+            let mut sums = [0u32; 3];
+            for &(dx, dy) in &offsets {
+                let px = sample(&data, x as isize + dx, y as isize + dy, width, height, border);
+                for c in 0..3 {
+                    sums[c] += px[c] as u32;
+                }
+            }
+
+            let idx = (y * width + x) * 4;
+            for c in 0..3 {
+                output[idx + c] = ((sums[c] + length / 2) / length) as u8;
+            }
+            // alpha was copied with the clone
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+// blends identity with the laplacian sharpen kernel: center 1 + 4s, the four
+// direct neighbours -s. 0 leaves the image alone, ~1 matches the usual fixed
+// [0,-1,0,-1,5,-1,0,-1,0] kernel, anything past that gets haloey fast
+#[wasm_bindgen]
+pub fn sharpen(image_data: &ImageData, strength: f64) -> Result<ImageData, JsValue> {
+    sharpen_checked(image_data, strength, BorderMode::Clamp)
+}
+
+// sharpen with a choice of border mode, sharpen itself clamps
+#[wasm_bindgen]
+pub fn sharpen_border(image_data: &ImageData, strength: f64, border: u8) -> Result<ImageData, JsValue> {
+    sharpen_checked(image_data, strength, BorderMode::from_u8(border)?)
+}
+
+fn sharpen_checked(image_data: &ImageData, strength: f64, border: BorderMode) -> Result<ImageData, JsValue> {
+    if !(strength.is_finite() && strength >= 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "sharpen strength must be a non-negative number"));
+    }
+
+    let kernel = sharpen_kernel(strength as f32);
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let output = convolve3x3(&image_data.data(), width, height, &kernel, 1.0, 0.0, border);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+fn sharpen_kernel(s: f32) -> [f32; 9] {
+    [0.0, -s, 0.0, -s, 1.0 + 4.0 * s, -s, 0.0, -s, 0.0]
+}
+
+// general 3x3 convolution on r, g and b: out = sum(kernel * px) / divisor + bias
+// clamped to 0..255. taps past the edge follow `border`, alpha is copied through
+pub(crate) fn convolve3x3(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    kernel: &[f32; 9],
+    divisor: f32,
+    bias: f32,
+    border: BorderMode,
+) -> Vec<u8> {
+    let mut out = vec![0u8; data.len()];
+    // x + kx - 1 for a tap is entry x + kx
+    let xs = border.resolve_range(width, 1);
+    let ys = border.resolve_range(height, 1);
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut acc = [0f32; 3];
+
+            for ky in 0..3 {
+                let Some(py) = ys[y + ky] else { continue };
+                for kx in 0..3 {
+                    let Some(px) = xs[x + kx] else { continue };
+                    let idx = (py * width + px) * 4;
+                    let weight = kernel[ky * 3 + kx];
+                    for (c, sum) in acc.iter_mut().enumerate() {
+                        *sum += data[idx + c] as f32 * weight;
+                    }
+                }
+            }
+
+            let idx = (y * width + x) * 4;
+            for (c, sum) in acc.iter().enumerate() {
+                out[idx + c] = (sum / divisor + bias).round().clamp(0.0, 255.0) as u8;
+            }
+            out[idx + 3] = data[idx + 3];
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convolve3x3_follows_border_mode() {
+        let (width, height) = (5, 4);
+        let data: Vec<u8> = (0..width * height).flat_map(|_| [90, 90, 90, 255]).collect();
+        let box_kernel = [1.0; 9];
+
+        // a flat image stays flat when the border repeats it
+        for border in [BorderMode::Clamp, BorderMode::Reflect, BorderMode::Wrap] {
+            let out = convolve3x3(&data, width, height, &box_kernel, 9.0, 0.0, border);
+            assert_eq!(out, data);
+        }
+
+        // zero padding: a corner only sees 4 of its 9 taps, an edge pixel 6
+        let out = convolve3x3(&data, width, height, &box_kernel, 9.0, 0.0, BorderMode::Zero);
+        assert_eq!(&out[..4], &[40, 40, 40, 255]);
+        assert_eq!(&out[4..8], &[60, 60, 60, 255]);
+        assert_eq!(&out[(width + 1) * 4..(width + 1) * 4 + 4], &[90, 90, 90, 255]);
+    }
+
+    #[test]
+    fn sharpen_strength_zero_is_identity() {
+        let (width, height) = (6, 5);
+        let data: Vec<u8> = (0..width * height * 4).map(|i| (i * 37 % 256) as u8).collect();
+
+        for border in [BorderMode::Zero, BorderMode::Clamp, BorderMode::Reflect, BorderMode::Wrap] {
+            let out = convolve3x3(&data, width, height, &sharpen_kernel(0.0), 1.0, 0.0, border);
+            assert_eq!(out, data);
+        }
+    }
+}
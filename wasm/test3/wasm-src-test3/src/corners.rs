@@ -0,0 +1,207 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::border::BorderMode;
+use crate::{gaussian_kernel, gradient, grayscale_into, EdgeOperator};
+
+#[wasm_bindgen]
+pub struct CornerResult {
+    corners: Vec<f32>,
+    image: ImageData,
+}
+
+#[wasm_bindgen]
+impl CornerResult {
+    // flat (x, y, score) triples, strongest first
+    #[wasm_bindgen(getter)]
+    pub fn corners(&self) -> Vec<f32> {
+        self.corners.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> u32 {
+        (self.corners.len() / 3) as u32
+    }
+
+    // the original with a red cross on every corner
+    #[wasm_bindgen(getter)]
+    pub fn image(&self) -> ImageData {
+        self.image.clone()
+    }
+}
+
+// sigma of the structure tensor window
+const TENSOR_SIGMA: f32 = 1.0;
+// half length of the marker cross arms
+const MARKER_ARM: isize = 3;
+
+// Harris corners: sobel gradients -> gaussian weighted structure tensor ->
+// R = det(M) - k * trace(M)^2. k is usually 0.04-0.06. threshold is a
+// fraction (0..1) of the strongest response, so one value works across
+// lighting changes. a corner also has to be the maximum within nms_radius.
+// everything is O(pixels) apart from the suppression, which only runs on
+// pixels that already passed the threshold
+#[wasm_bindgen]
+pub fn harris_corners(image_data: &ImageData, k: f32, threshold: f32, nms_radius: u32) -> Result<CornerResult, JsValue> {
+    harris_checked(image_data, k, threshold, nms_radius, BorderMode::Clamp)
+}
+
+// harris_corners with a choice of border mode for the tensor smoothing,
+// harris_corners itself clamps. wrap finds corners that straddle the seam of a
+// tileable texture
+#[wasm_bindgen]
+pub fn harris_corners_border(
+    image_data: &ImageData,
+    k: f32,
+    threshold: f32,
+    nms_radius: u32,
+    border: u8,
+) -> Result<CornerResult, JsValue> {
+    harris_checked(image_data, k, threshold, nms_radius, BorderMode::from_u8(border)?)
+}
+
+fn harris_checked(
+    image_data: &ImageData,
+    k: f32,
+    threshold: f32,
+    nms_radius: u32,
+    border: BorderMode,
+) -> Result<CornerResult, JsValue> {
+    if !(k.is_finite() && k > 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "k must be a positive number"));
+    }
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(error(ErrorCode::InvalidParameter, "threshold must be between 0 and 1"));
+    }
+
+    let mut data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut gray = vec![0u8; data.len()];
+    grayscale_into(&data, &mut gray);
+
+    // tensor entries, borders stay 0 like the other sobel paths
+    let mut xx = vec![0f32; width * height];
+    let mut yy = vec![0f32; width * height];
+    let mut xy = vec![0f32; width * height];
+    for y in 1..height.saturating_sub(1) {
+        for x in 1..width.saturating_sub(1) {
+            let (gx, gy) = gradient(&gray, width, x, y, EdgeOperator::Sobel);
+            // /8 brings sobel back to intensity units
+            let (gx, gy) = (gx as f32 / 8.0, gy as f32 / 8.0);
+            let i = y * width + x;
+            xx[i] = gx * gx;
+            yy[i] = gy * gy;
+            xy[i] = gx * gy;
+        }
+    }
+
+    let kernel = gaussian_kernel(TENSOR_SIGMA);
+    let mut scratch = vec![0f32; width * height];
+    for plane in [&mut xx, &mut yy, &mut xy] {
+        smooth(plane, &mut scratch, width, height, &kernel, border);
+    }
+
+    let response: Vec<f32> = (0..width * height)
+        .map(|i| {
+            let trace = xx[i] + yy[i];
+            xx[i] * yy[i] - xy[i] * xy[i] - k * trace * trace
+        })
+        .collect();
+
+    let max = response.iter().copied().fold(0f32, f32::max);
+    let cutoff = max * threshold;
+    let radius = nms_radius as isize;
+
+    let mut found: Vec<(usize, usize, f32)> = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let score = response[y * width + x];
+            if score <= 0.0 || score < cutoff {
+                continue;
+            }
+            if is_local_max(&response, width, height, x, y, radius) {
+                found.push((x, y, score));
+            }
+        }
+    }
+    found.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+    for &(x, y, _) in &found {
+        draw_cross(&mut data, width, height, x, y);
+    }
+
+    Ok(CornerResult {
+        corners: found.iter().flat_map(|&(x, y, s)| [x as f32, y as f32, s]).collect(),
+        image: ImageData::new_with_u8_clamped_array_and_sh(
+            wasm_bindgen::Clamped(&data),
+            width as u32,
+            height as u32,
+        )?,
+    })
+}
+
+// same tie-break as sobel_thin_into: >= against earlier pixels, > against later
+// ones, so a plateau keeps exactly one
+fn is_local_max(response: &[f32], width: usize, height: usize, x: usize, y: usize, radius: isize) -> bool {
+    let score = response[y * width + x];
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let (nx, ny) = (x as isize + dx, y as isize + dy);
+            if (dx, dy) == (0, 0) || nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                continue;
+            }
+            let other = response[ny as usize * width + nx as usize];
+            let earlier = (dy, dx) < (0, 0);
+            if other > score || (!earlier && other == score) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+// separable gaussian on a float plane, taps past the edge follow `border`
+// (zero padding reads 0)
+fn smooth(plane: &mut [f32], scratch: &mut [f32], width: usize, height: usize, kernel: &[f32], border: BorderMode) {
+    // x + i - radius for tap i is entry x + i
+    let radius = kernel.len() / 2;
+    let xs = border.resolve_range(width, radius);
+    let ys = border.resolve_range(height, radius);
+    for y in 0..height {
+        for x in 0..width {
+            let mut acc = 0f32;
+            for (i, w) in kernel.iter().enumerate() {
+                if let Some(sx) = xs[x + i] {
+                    acc += plane[y * width + sx] * w;
+                }
+            }
+            scratch[y * width + x] = acc;
+        }
+    }
+    for y in 0..height {
+        for x in 0..width {
+            let mut acc = 0f32;
+            for (i, w) in kernel.iter().enumerate() {
+                if let Some(sy) = ys[y + i] {
+                    acc += scratch[sy * width + x] * w;
+                }
+            }
+            plane[y * width + x] = acc;
+        }
+    }
+}
+
+fn draw_cross(data: &mut [u8], width: usize, height: usize, x: usize, y: usize) {
+    for d in -MARKER_ARM..=MARKER_ARM {
+        for (px, py) in [(x as isize + d, y as isize), (x as isize, y as isize + d)] {
+            if px < 0 || py < 0 || px >= width as isize || py >= height as isize {
+                continue;
+            }
+            let idx = (py as usize * width + px as usize) * 4;
+            data[idx..idx + 4].copy_from_slice(&[255, 0, 0, 255]);
+        }
+    }
+}
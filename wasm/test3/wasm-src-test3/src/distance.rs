@@ -0,0 +1,159 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+
+// distance from every pixel to the nearest foreground (non-black) pixel,
+// scaled so the farthest pixel is 255. foreground itself is 0.
+// two-pass chamfer: forward pass pulls distances from the top/left
+// neighbours, backward pass from the bottom/right ones. steps cost 1 and
+// diagonals sqrt(2), which stays within ~8% of the true euclidean distance
+#[wasm_bindgen]
+pub fn distance_transform(image_data: &ImageData) -> Result<ImageData, JsValue> {
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let foreground = foreground_mask(&image_data.data());
+    let distances = chamfer(&foreground, width, height);
+    distance_image(&distances, width, height)
+}
+
+// the inside version: for every foreground pixel, the chamfer distance to the
+// nearest background pixel, background itself is 0. raw distances in pixels,
+// one per pixel as a Float32Array. an image with no background at all comes
+// back as Infinity everywhere
+#[wasm_bindgen]
+pub fn distance_to_background(image_data: &ImageData) -> Vec<f32> {
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let background: Vec<bool> = foreground_mask(&image_data.data()).iter().map(|&f| !f).collect();
+    chamfer(&background, width, height)
+}
+
+// distance_to_background as grayscale, brightest at the pixel deepest inside a
+// shape. handy as a preview before skeletonize
+#[wasm_bindgen]
+pub fn distance_to_background_image(image_data: &ImageData) -> Result<ImageData, JsValue> {
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    distance_image(&distance_to_background(image_data), width, height)
+}
+
+// non-black is foreground, which is what the threshold and edge outputs give
+pub(crate) fn foreground_mask(data: &[u8]) -> Vec<bool> {
+    data.chunks_exact(4)
+        .map(|px| px[0] != 0 || px[1] != 0 || px[2] != 0)
+        .collect()
+}
+
+// scales distances so the farthest finite one is 255
+fn distance_image(distances: &[f32], width: usize, height: usize) -> Result<ImageData, JsValue> {
+    // no source pixels at all leaves everything infinitely far away
+    let max = distances.iter().copied().filter(|d| d.is_finite()).fold(0f32, f32::max);
+    let mut output = vec![0u8; width * height * 4];
+    for (px, &d) in output.chunks_exact_mut(4).zip(distances) {
+        let value = if !d.is_finite() {
+            255
+        } else if max > 0.0 {
+            (d / max * 255.0).round() as u8
+        } else {
+            0
+        };
+        px[0] = value;
+        px[1] = value;
+        px[2] = value;
+        px[3] = 255;
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+// distance from each pixel to the nearest `source` pixel, sources are 0
+fn chamfer(sources: &[bool], width: usize, height: usize) -> Vec<f32> {
+    const DIAGONAL: f32 = std::f32::consts::SQRT_2;
+
+    let mut dist: Vec<f32> = sources
+        .iter()
+        .map(|&f| if f { 0.0 } else { f32::INFINITY })
+        .collect();
+
+    // (dx, dy, cost) of the already visited half of the 3x3 neighbourhood
+    let forward = [(-1, 0, 1.0), (-1, -1, DIAGONAL), (0, -1, 1.0), (1, -1, DIAGONAL)];
+    let backward = [(1, 0, 1.0), (1, 1, DIAGONAL), (0, 1, 1.0), (-1, 1, DIAGONAL)];
+
+    let mut relax = |x: usize, y: usize, mask: &[(isize, isize, f32); 4]| {
+        let i = y * width + x;
+        for &(dx, dy, cost) in mask {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                continue;
+            }
+            let candidate = dist[ny as usize * width + nx as usize] + cost;
+            if candidate < dist[i] {
+                dist[i] = candidate;
+            }
+        }
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            relax(x, y, &forward);
+        }
+    }
+    for y in (0..height).rev() {
+        for x in (0..width).rev() {
+            relax(x, y, &backward);
+        }
+    }
+
+    dist
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances_radiate_from_a_single_pixel() {
+        let (width, height) = (7, 5);
+        let (sx, sy) = (3, 2);
+        let mut sources = vec![false; width * height];
+        sources[sy * width + sx] = true;
+        let dist = chamfer(&sources, width, height);
+        let at = |x: usize, y: usize| dist[y * width + x];
+
+        assert_eq!(at(sx, sy), 0.0);
+        for (x, y) in [(sx - 1, sy), (sx + 1, sy), (sx, sy - 1), (sx, sy + 1)] {
+            assert_eq!(at(x, y), 1.0);
+        }
+        for (x, y) in [(sx - 1, sy - 1), (sx + 1, sy - 1), (sx - 1, sy + 1), (sx + 1, sy + 1)] {
+            assert!((at(x, y) - std::f32::consts::SQRT_2).abs() < 1e-6);
+        }
+
+        // one step further out along any row or column never gets closer
+        for y in 0..height {
+            for x in 0..width {
+                let d = at(x, y);
+                assert!(d.is_finite());
+                if x > sx {
+                    assert!(d > at(x - 1, y));
+                }
+                if x < sx {
+                    assert!(d > at(x + 1, y));
+                }
+                if y > sy {
+                    assert!(d > at(x, y - 1));
+                }
+                if y < sy {
+                    assert!(d > at(x, y + 1));
+                }
+            }
+        }
+        // corner: 2 diagonal steps and 1 straight one
+        assert!((at(0, 0) - (2.0 * std::f32::consts::SQRT_2 + 1.0)).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,180 @@
+use js_sys::{Float32Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::{blur_into, edges_into, EdgeOperator};
+
+// peaks have to beat everything within this many bins in rho and theta
+const PEAK_RADIUS: isize = 2;
+
+// straight lines via the hough transform on the sobel edge map (same edges as
+// edge_detection, with edge_threshold). lines are x*cos(theta) + y*sin(theta) = rho
+// with the origin at the top-left pixel, theta in [0, pi) split into
+// theta_steps bins and rho in 1px bins. returns
+//   lines: Float32Array of (rho, theta in radians, votes) triples, most votes first
+//   image: the source with every line drawn in red across the whole image
+#[wasm_bindgen]
+pub fn hough_lines(
+    image_data: &ImageData,
+    edge_threshold: u8,
+    vote_threshold: u32,
+    theta_steps: u32,
+) -> Result<JsValue, JsValue> {
+    if theta_steps == 0 {
+        return Err(error(ErrorCode::InvalidParameter, "theta_steps must be at least 1"));
+    }
+
+    let mut data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut blurred = vec![0u8; data.len()];
+    blur_into(&data, &mut blurred, width, height);
+    let mut edges = vec![0u8; data.len()];
+    edges_into(&blurred, &mut edges, width, height, edge_threshold, EdgeOperator::Sobel);
+
+    let thetas = theta_steps as usize;
+    let trig: Vec<(f64, f64)> = (0..thetas)
+        .map(|t| (t as f64 * std::f64::consts::PI / thetas as f64).sin_cos())
+        .map(|(sin, cos)| (cos, sin))
+        .collect();
+
+    // |rho| can't exceed the diagonal, bins are offset so rho = -diagonal is 0
+    let diagonal = ((width * width + height * height) as f64).sqrt().ceil() as usize;
+    let rhos = diagonal * 2 + 1;
+    let mut votes = vec![0u32; rhos * thetas];
+
+    for y in 0..height {
+        for x in 0..width {
+            if edges[(y * width + x) * 4] != 255 {
+                continue;
+            }
+            for (t, &(cos, sin)) in trig.iter().enumerate() {
+                let rho = (x as f64 * cos + y as f64 * sin).round() as isize + diagonal as isize;
+                votes[rho as usize * thetas + t] += 1;
+            }
+        }
+    }
+
+    let mut lines: Vec<(f64, f64, u32)> = Vec::new();
+    for r in 0..rhos {
+        for t in 0..thetas {
+            let count = votes[r * thetas + t];
+            if count >= vote_threshold.max(1) && is_peak(&votes, rhos, thetas, r, t) {
+                let theta = t as f64 * std::f64::consts::PI / thetas as f64;
+                lines.push((r as f64 - diagonal as f64, theta, count));
+            }
+        }
+    }
+    lines.sort_by_key(|line| std::cmp::Reverse(line.2));
+
+    for &(rho, theta, _) in &lines {
+        if let Some((x0, y0, x1, y1)) = clip_line(rho, theta, width, height) {
+            draw_line(&mut data, width, (x0, y0), (x1, y1));
+        }
+    }
+
+    let flat: Vec<f32> = lines
+        .iter()
+        .flat_map(|&(rho, theta, count)| [rho as f32, theta as f32, count as f32])
+        .collect();
+    let image = ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        width as u32,
+        height as u32,
+    )?;
+
+    let result = Object::new();
+    Reflect::set(&result, &"lines".into(), &Float32Array::from(&flat[..]))?;
+    Reflect::set(&result, &"image".into(), &image)?;
+    Ok(result.into())
+}
+
+// local maximum in the accumulator, >= before and > after so a plateau keeps one
+fn is_peak(votes: &[u32], rhos: usize, thetas: usize, r: usize, t: usize) -> bool {
+    let count = votes[r * thetas + t];
+    for dr in -PEAK_RADIUS..=PEAK_RADIUS {
+        for dt in -PEAK_RADIUS..=PEAK_RADIUS {
+            let (nr, nt) = (r as isize + dr, t as isize + dt);
+            if (dr, dt) == (0, 0) || nr < 0 || nt < 0 || nr >= rhos as isize || nt >= thetas as isize {
+                continue;
+            }
+            let other = votes[nr as usize * thetas + nt as usize];
+            let earlier = (dr, dt) < (0, 0);
+            if other > count || (!earlier && other == count) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+// endpoints of the line inside [0, width - 1] x [0, height - 1], None if it
+// misses the image. liang-barsky on a segment longer than the image, so
+// vertical (theta = 0) and horizontal lines need no special cases
+fn clip_line(rho: f64, theta: f64, width: usize, height: usize) -> Option<(isize, isize, isize, isize)> {
+    let (sin, cos) = theta.sin_cos();
+    // closest point to the origin, then walk along the line direction
+    let (px, py) = (rho * cos, rho * sin);
+    let (dx, dy) = (-sin, cos);
+    let reach = (width + height) as f64 * 2.0;
+    let (sx, sy) = (px - dx * reach, py - dy * reach);
+    let (ex, ey) = (dx * 2.0 * reach, dy * 2.0 * reach);
+
+    let (max_x, max_y) = ((width as f64 - 1.0).max(0.0), (height as f64 - 1.0).max(0.0));
+    let mut t0 = 0f64;
+    let mut t1 = 1f64;
+    for (p, q) in [(-ex, sx), (ex, max_x - sx), (-ey, sy), (ey, max_y - sy)] {
+        if p == 0.0 {
+            // parallel to this edge, either fully inside or fully out
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let r = q / p;
+            if p < 0.0 {
+                t0 = t0.max(r);
+            } else {
+                t1 = t1.min(r);
+            }
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+
+    Some((
+        (sx + ex * t0).round() as isize,
+        (sy + ey * t0).round() as isize,
+        (sx + ex * t1).round() as isize,
+        (sy + ey * t1).round() as isize,
+    ))
+}
+
+// bresenham, endpoints are already clipped to the image
+fn draw_line(data: &mut [u8], width: usize, from: (isize, isize), to: (isize, isize)) {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let step_x = if x < to.0 { 1 } else { -1 };
+    let step_y = if y < to.1 { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    loop {
+        let idx = (y as usize * width + x as usize) * 4;
+        data[idx..idx + 4].copy_from_slice(&[255, 0, 0, 255]);
+        if (x, y) == to {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += step_x;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += step_y;
+        }
+    }
+}
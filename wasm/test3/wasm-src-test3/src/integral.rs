@@ -0,0 +1,143 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+// summed-area tables of every channel and of every channel squared, built once
+// so any rectangle's sum / mean / variance is four lookups. u64 accumulators,
+// a 4K frame of 255s squared is ~5e11 which is nowhere near the limit.
+// query results are [r, g, b, a]
+#[wasm_bindgen]
+pub struct IntegralImage {
+    width: usize,
+    height: usize,
+    // (width + 1) x (height + 1) x 4 with a zero row/column so lookups need no branches
+    sums: Vec<u64>,
+    squares: Vec<u64>,
+}
+
+#[wasm_bindgen]
+impl IntegralImage {
+    #[wasm_bindgen(constructor)]
+    pub fn new(image_data: &ImageData) -> IntegralImage {
+        let data = image_data.data();
+        let width = image_data.width() as usize;
+        let height = image_data.height() as usize;
+        let stride = (width + 1) * 4;
+
+        let mut sums = vec![0u64; stride * (height + 1)];
+        let mut squares = vec![0u64; stride * (height + 1)];
+        for y in 0..height {
+            let mut row_sum = [0u64; 4];
+            let mut row_square = [0u64; 4];
+            for x in 0..width {
+                let src = (y * width + x) * 4;
+                let above = y * stride + (x + 1) * 4;
+                let dst = above + stride;
+                for c in 0..4 {
+                    let v = data[src + c] as u64;
+                    row_sum[c] += v;
+                    row_square[c] += v * v;
+                    sums[dst + c] = sums[above + c] + row_sum[c];
+                    squares[dst + c] = squares[above + c] + row_square[c];
+                }
+            }
+        }
+
+        IntegralImage { width, height, sums, squares }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width as u32
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height as u32
+    }
+
+    // the rectangle has to be non-empty and fit inside the image, same as crop
+    pub fn sum(&self, x: u32, y: u32, w: u32, h: u32) -> Result<Vec<f64>, JsValue> {
+        let rect = self.rect(x, y, w, h)?;
+        Ok(window(&self.sums, self.width, rect).iter().map(|&s| s as f64).collect())
+    }
+
+    pub fn mean(&self, x: u32, y: u32, w: u32, h: u32) -> Result<Vec<f64>, JsValue> {
+        let rect = self.rect(x, y, w, h)?;
+        let count = (w as u64 * h as u64) as f64;
+        Ok(window(&self.sums, self.width, rect).iter().map(|&s| s as f64 / count).collect())
+    }
+
+    // population variance, E[x^2] - E[x]^2
+    pub fn variance(&self, x: u32, y: u32, w: u32, h: u32) -> Result<Vec<f64>, JsValue> {
+        let rect = self.rect(x, y, w, h)?;
+        let count = (w as u64 * h as u64) as f64;
+        let sums = window(&self.sums, self.width, rect);
+        let squares = window(&self.squares, self.width, rect);
+        Ok(sums
+            .iter()
+            .zip(squares)
+            .map(|(&s, sq)| {
+                let mean = s as f64 / count;
+                (sq as f64 / count - mean * mean).max(0.0)
+            })
+            .collect())
+    }
+
+    // flat (2 * radius + 1)^2 mean of all four channels, O(1) per pixel whatever
+    // the radius. windows are cut off at the borders and only count pixels
+    // inside the image, radius 0 gives the original back
+    pub fn box_blur(&self, radius: u32) -> Result<ImageData, JsValue> {
+        let radius = radius as usize;
+        let mut output = vec![0u8; self.width * self.height * 4];
+
+        for y in 0..self.height {
+            let y0 = y.saturating_sub(radius);
+            let y1 = (y + radius + 1).min(self.height);
+            for x in 0..self.width {
+                let x0 = x.saturating_sub(radius);
+                let x1 = (x + radius + 1).min(self.width);
+                let count = ((x1 - x0) * (y1 - y0)) as u64;
+                let sums = window(&self.sums, self.width, (x0, y0, x1, y1));
+
+                let dst = (y * self.width + x) * 4;
+                for (value, s) in output[dst..dst + 4].iter_mut().zip(sums) {
+                    // integer rounding, sum / count to the nearest
+                    *value = ((s + count / 2) / count) as u8;
+                }
+            }
+        }
+
+        ImageData::new_with_u8_clamped_array_and_sh(
+            wasm_bindgen::Clamped(&output),
+            self.width as u32,
+            self.height as u32,
+        )
+    }
+}
+
+impl IntegralImage {
+    // (x0, y0, x1, y1) with exclusive ends
+    fn rect(&self, x: u32, y: u32, w: u32, h: u32) -> Result<(usize, usize, usize, usize), JsValue> {
+        if w == 0 || h == 0 {
+            return Err(error(ErrorCode::InvalidDimensions, "rectangle size must be non-zero"));
+        }
+        // u64 so a huge x + w can't wrap around and sneak past the check
+        if x as u64 + w as u64 > self.width as u64 || y as u64 + h as u64 > self.height as u64 {
+            return Err(error(ErrorCode::OutOfBounds, "rectangle is outside the image"));
+        }
+        Ok((x as usize, y as usize, (x + w) as usize, (y + h) as usize))
+    }
+}
+
+// per-channel total over [x0, x1) x [y0, y1)
+fn window(table: &[u64], width: usize, (x0, y0, x1, y1): (usize, usize, usize, usize)) -> [u64; 4] {
+    let stride = (width + 1) * 4;
+    let mut out = [0u64; 4];
+    for (c, value) in out.iter_mut().enumerate() {
+        *value = table[y1 * stride + x1 * 4 + c] + table[y0 * stride + x0 * 4 + c]
+            - table[y0 * stride + x1 * 4 + c]
+            - table[y1 * stride + x0 * 4 + c];
+    }
+    out
+}
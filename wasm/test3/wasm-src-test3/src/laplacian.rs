@@ -0,0 +1,165 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::border::BorderMode;
+use crate::{gaussian_blur_in_place, grayscale_into};
+
+// second derivative edge detectors, to compare against sobel in the demo grid
+
+// how the signed DoG response gets turned into pixels
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DogOutput {
+    ZeroCrossing = 0, // binary, 255 where the response changes sign
+    Magnitude = 1,    // |response| normalized to the strongest pixel
+    Offset = 2,       // 128 + response, negatives darker and positives lighter
+    Lines = 3,        // black lines on white where the response is strongly negative
+}
+
+// Lines ignores responses weaker than this fraction of the strongest one,
+// otherwise flat areas turn into speckle
+const LINE_THRESHOLD: f64 = 0.1;
+
+const LAPLACIAN_3: [i32; 9] = [0, 1, 0, 1, -4, 1, 0, 1, 0];
+const LAPLACIAN_5: [i32; 25] = [
+    0, 0, 1, 0, 0,
+    0, 1, 2, 1, 0,
+    1, 2, -16, 2, 1,
+    0, 1, 2, 1, 0,
+    0, 0, 1, 0, 0,
+];
+
+// kernel_size is 3 or 5, output is |response| so both signs show up as edges.
+// borders clamp
+#[wasm_bindgen]
+pub fn laplacian(image_data: &ImageData, kernel_size: u8) -> Result<ImageData, JsValue> {
+    laplacian_checked(image_data, kernel_size, BorderMode::Clamp)
+}
+
+// laplacian with a choice of border mode (see border.rs). zero mode outlines
+// any image that isn't black at the edge
+#[wasm_bindgen]
+pub fn laplacian_border(image_data: &ImageData, kernel_size: u8, border: u8) -> Result<ImageData, JsValue> {
+    laplacian_checked(image_data, kernel_size, BorderMode::from_u8(border)?)
+}
+
+fn laplacian_checked(image_data: &ImageData, kernel_size: u8, border: BorderMode) -> Result<ImageData, JsValue> {
+    let (size, kernel): (usize, &[i32]) = match kernel_size {
+        3 => (3, &LAPLACIAN_3),
+        5 => (5, &LAPLACIAN_5),
+        _ => return Err(error(ErrorCode::InvalidParameter, "laplacian kernel_size must be 3 or 5")),
+    };
+
+    let data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut gray = vec![0u8; data.len()];
+    grayscale_into(&data, &mut gray);
+
+    // 5x5 center weight is 4x the 3x3 one, scale back so both look alike
+    let scale = 4.0 / -kernel[kernel.len() / 2] as f64;
+    let radius = size / 2;
+    // x + kx - radius for a tap is entry x + kx
+    let xs = border.resolve_range(width, radius);
+    let ys = border.resolve_range(height, radius);
+    let mut output = vec![0u8; data.len()];
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut acc = 0i32;
+            for ky in 0..size {
+                let Some(py) = ys[y + ky] else { continue };
+                for kx in 0..size {
+                    let Some(px) = xs[x + kx] else { continue };
+                    acc += gray[(py * width + px) * 4] as i32 * kernel[ky * size + kx];
+                }
+            }
+
+            let value = (acc.abs() as f64 * scale).round().min(255.0) as u8;
+            let idx = (y * width + x) * 4;
+            output[idx] = value;
+            output[idx + 1] = value;
+            output[idx + 2] = value;
+            output[idx + 3] = 255;
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+// blur(sigma1) - blur(sigma2) on the grayscale image, with signed arithmetic.
+// sigma2 has to be the wider one
+#[wasm_bindgen]
+pub fn difference_of_gaussians(
+    image_data: &ImageData,
+    sigma1: f32,
+    sigma2: f32,
+    output_mode: DogOutput,
+) -> Result<ImageData, JsValue> {
+    if !(sigma1.is_finite() && sigma1 > 0.0 && sigma2.is_finite() && sigma2 > 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "sigmas must be positive numbers"));
+    }
+    if sigma2 <= sigma1 {
+        return Err(error(ErrorCode::InvalidParameter, "sigma2 must be larger than sigma1"));
+    }
+
+    let data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut narrow = vec![0u8; data.len()];
+    grayscale_into(&data, &mut narrow);
+    let mut wide = narrow.clone();
+    let mut scratch = vec![0u8; data.len()];
+    gaussian_blur_in_place(&mut narrow, &mut scratch, width, height, sigma1);
+    gaussian_blur_in_place(&mut wide, &mut scratch, width, height, sigma2);
+
+    // i16 so negatives don't wrap around like they would in u8
+    let diff: Vec<i16> = (0..width * height)
+        .map(|i| narrow[i * 4] as i16 - wide[i * 4] as i16)
+        .collect();
+    let max_abs = diff.iter().map(|d| d.abs()).max().unwrap_or(0).max(1) as f64;
+    // at least a couple of gray levels so a featureless image stays blank
+    let line_cutoff = (max_abs * LINE_THRESHOLD).max(2.0);
+
+    let mut output = vec![0u8; data.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let i = y * width + x;
+            let d = diff[i];
+
+            let value = match output_mode {
+                DogOutput::ZeroCrossing => {
+                    // strict sign change against the right or bottom neighbour,
+                    // flat areas (0 next to 0) are not crossings
+                    let right = if x + 1 < width { diff[i + 1] } else { d };
+                    let below = if y + 1 < height { diff[i + width] } else { d };
+                    let crosses = |n: i16| (d > 0 && n < 0) || (d < 0 && n > 0);
+                    if crosses(right) || crosses(below) { 255 } else { 0 }
+                }
+                DogOutput::Magnitude => (d.abs() as f64 / max_abs * 255.0).round() as u8,
+                DogOutput::Offset => (128.0 + d as f64 / max_abs * 127.0).round() as u8,
+                // the dark side of an edge comes out negative
+                DogOutput::Lines => if (d as f64) < -line_cutoff { 0 } else { 255 },
+            };
+
+            let idx = i * 4;
+            output[idx] = value;
+            output[idx + 1] = value;
+            output[idx + 2] = value;
+            output[idx + 3] = 255;
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
@@ -1,76 +1,448 @@
 use wasm_bindgen::prelude::*;
 use web_sys::ImageData;
+use wasm_src_common::alpha::{has_translucency, premultiply_pixels, unpremultiply_pixels};
+use wasm_src_common::cancel::{self, CancellationToken};
+use wasm_src_common::error::{error, ErrorCode};
+
+use border::BorderMode;
+
+mod bilateral;
+mod border;
+mod buffer;
+mod components;
+mod convolve;
+mod corners;
+mod distance;
+mod hough;
+mod integral;
+mod kuwahara;
+mod laplacian;
+mod linear;
+mod overlay;
+mod pipeline;
+mod pyramid;
+mod seam;
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+mod simd;
+mod skeleton;
+mod svg;
+mod template;
+mod threshold;
+mod tiled;
+
+// gradient operators for the edge path, sobel is the default everywhere
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EdgeOperator {
+    Sobel = 0,
+    Scharr = 1,
+    Prewitt = 2,
+    Roberts = 3,
+}
+
+// kernel tables, row-major
+const SOBEL_X: [i32; 9] = [-1, 0, 1, -2, 0, 2, -1, 0, 1];
+const SOBEL_Y: [i32; 9] = [-1, -2, -1, 0, 0, 0, 1, 2, 1];
+const SCHARR_X: [i32; 9] = [-3, 0, 3, -10, 0, 10, -3, 0, 3];
+const SCHARR_Y: [i32; 9] = [-3, -10, -3, 0, 0, 0, 3, 10, 3];
+const PREWITT_X: [i32; 9] = [-1, 0, 1, -1, 0, 1, -1, 0, 1];
+const PREWITT_Y: [i32; 9] = [-1, -1, -1, 0, 0, 0, 1, 1, 1];
+const ROBERTS_X: [i32; 4] = [1, 0, 0, -1];
+const ROBERTS_Y: [i32; 4] = [0, 1, -1, 0];
+
+impl EdgeOperator {
+    // (size, x kernel, y kernel)
+    fn kernels(self) -> (usize, &'static [i32], &'static [i32]) {
+        match self {
+            EdgeOperator::Sobel => (3, &SOBEL_X, &SOBEL_Y),
+            EdgeOperator::Scharr => (3, &SCHARR_X, &SCHARR_Y),
+            EdgeOperator::Prewitt => (3, &PREWITT_X, &PREWITT_Y),
+            EdgeOperator::Roberts => (2, &ROBERTS_X, &ROBERTS_Y),
+        }
+    }
+
+    // rescales magnitudes so a hard vertical step gives the same response as
+    // sobel (4 * 255), that way one threshold works for every operator
+    fn scale(self) -> f64 {
+        match self {
+            EdgeOperator::Sobel => 1.0,
+            EdgeOperator::Scharr => 4.0 / 16.0,
+            EdgeOperator::Prewitt => 4.0 / 3.0,
+            EdgeOperator::Roberts => 4.0 / std::f64::consts::SQRT_2,
+        }
+    }
+}
 
 // Sobel edge detection, blur first to reduce noise
 #[wasm_bindgen]
 pub fn edge_detection(image_data: ImageData) -> Result<ImageData, JsValue> {
-    let blurred = blur(&image_data)?;
-    let data = blurred.data().0;
-    let width = blurred.width() as usize;
-    let height = blurred.height() as usize;
-    
+    edge_detection_checked(image_data, None)
+}
+
+// edge_detection that stops with a CANCELLED error once `cancel` is tripped,
+// see cancel.rs in wasm-src-common
+#[wasm_bindgen]
+pub fn edge_detection_cancellable(image_data: ImageData, cancel: &CancellationToken) -> Result<ImageData, JsValue> {
+    edge_detection_checked(image_data, Some(cancel))
+}
+
+fn edge_detection_checked(image_data: ImageData, cancel: Option<&CancellationToken>) -> Result<ImageData, JsValue> {
+    let data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut blurred = vec![0u8; data.len()];
+    blur_into(&data, &mut blurred, width, height);
+    cancel::check(cancel)?;
+
     let mut output = vec![0u8; data.len()];
-    
-    // Sobel kernels for gradient calculation
-    let sobel_x: [[i32; 3]; 3] = [[-1, 0, 1], 
-                                  [-2, 0, 2], 
-                                  [-1, 0, 1]];
-    let sobel_y: [[i32; 3]; 3] = [[-1, -2, -1],
-                                  [0, 0, 0],
-                                  [1, 2, 1]];
     let thresh = 100; // REVIEW: maybe make this configurable later
-    
-    for y in 1..height-1 {
-        for x in 1..width-1 {
-            let mut gx = 0i32;
-            let mut gy = 0i32;
-            
-            for ky in 0..3 {
-                for kx in 0..3 {
-                    let px = x + kx - 1;
-                    let py = y + ky - 1;
-                    let idx = (py * width + px) * 4;
-                    let gray = data[idx] as i32; // already grayscale from blur
-                    
-                    gx += gray * sobel_x[ky][kx];
-                    gy += gray * sobel_y[ky][kx];
-                }
-            }
-            
+    edges_into_cancellable(&blurred, &mut output, width, height, thresh, EdgeOperator::Sobel, cancel);
+    cancel::check(cancel)?;
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+// edge_detection with a selectable gradient operator
+#[wasm_bindgen]
+pub fn edge_detection_operator(image_data: ImageData, operator: EdgeOperator) -> Result<ImageData, JsValue> {
+    let data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut blurred = vec![0u8; data.len()];
+    blur_into(&data, &mut blurred, width, height);
+
+    let mut output = vec![0u8; data.len()];
+    edges_into(&blurred, &mut output, width, height, 100, operator);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+// edge_detection with the pre-blur optional and a custom threshold. skipping the
+// blur keeps fine detail on images that are already clean
+#[wasm_bindgen]
+pub fn edge_detection_opts(image_data: ImageData, blur: bool, thresh: u8) -> Result<ImageData, JsValue> {
+    let data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    // edges_into only reads channel 0, so it still needs a grayscale buffer
+    let mut gray = vec![0u8; data.len()];
+    if blur {
+        blur_into(&data, &mut gray, width, height);
+    } else {
+        grayscale_into(&data, &mut gray);
+    }
+
+    let mut output = vec![0u8; data.len()];
+    edges_into(&gray, &mut output, width, height, thresh, EdgeOperator::Sobel);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+// same as edge_detection, but non-maximum suppression thins edges to 1px
+// (cleaner input for contour tracing)
+#[wasm_bindgen]
+pub fn edge_detection_thin(image_data: ImageData) -> Result<ImageData, JsValue> {
+    let data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut blurred = vec![0u8; data.len()];
+    blur_into(&data, &mut blurred, width, height);
+
+    let mut output = vec![0u8; data.len()];
+    sobel_thin_into(&blurred, &mut output, width, height, 100);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+// the steps edge_detection hides, for the teaching view: both sobel gradients
+// and the magnitude before thresholding
+#[wasm_bindgen]
+pub struct SobelComponents {
+    gx: ImageData,
+    gy: ImageData,
+    magnitude: ImageData,
+}
+
+#[wasm_bindgen]
+impl SobelComponents {
+    // horizontal gradient, 128 = flat, lighter = brighter to the right
+    #[wasm_bindgen(getter)]
+    pub fn gx(&self) -> ImageData {
+        self.gx.clone()
+    }
+
+    // vertical gradient, 128 = flat, lighter = brighter below
+    #[wasm_bindgen(getter)]
+    pub fn gy(&self) -> ImageData {
+        self.gy.clone()
+    }
+
+    // sqrt(gx^2 + gy^2) clamped to 255, what edge_detection thresholds
+    #[wasm_bindgen(getter)]
+    pub fn magnitude(&self) -> ImageData {
+        self.magnitude.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn sobel_components(image_data: &ImageData) -> Result<SobelComponents, JsValue> {
+    let data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut blurred = vec![0u8; data.len()];
+    blur_into(&data, &mut blurred, width, height);
+
+    // same transparent border as edges_into
+    let mut gx_out = vec![0u8; data.len()];
+    let mut gy_out = vec![0u8; data.len()];
+    let mut mag_out = vec![0u8; data.len()];
+
+    for y in 1..height.saturating_sub(1) {
+        for x in 1..width.saturating_sub(1) {
+            let (gx, gy) = gradient(&blurred, width, x, y, EdgeOperator::Sobel);
+            // sobel on 8-bit input stays within +-1020, /8 fits that around 128
+            let shifted = |g: i32| (128.0 + g as f64 / 8.0).round().clamp(0.0, 255.0) as u8;
             let magnitude = ((gx * gx + gy * gy) as f64).sqrt().round().min(255.0) as u8;
-            let edge = if magnitude > thresh { 255 } else { 0 };
-            
+
             let idx = (y * width + x) * 4;
-            output[idx] = edge;
-            output[idx + 1] = edge;
-            output[idx + 2] = edge;
-            output[idx + 3] = 255;
+            for (out, value) in [(&mut gx_out, shifted(gx)), (&mut gy_out, shifted(gy)), (&mut mag_out, magnitude)] {
+                out[idx] = value;
+                out[idx + 1] = value;
+                out[idx + 2] = value;
+                out[idx + 3] = 255;
+            }
         }
     }
-    
+
+    let to_image = |buffer: &[u8]| {
+        ImageData::new_with_u8_clamped_array_and_sh(
+            wasm_bindgen::Clamped(buffer),
+            width as u32,
+            height as u32,
+        )
+    };
+    Ok(SobelComponents {
+        gx: to_image(&gx_out)?,
+        gy: to_image(&gy_out)?,
+        magnitude: to_image(&mag_out)?,
+    })
+}
+
+// separable gaussian over all four channels, borders clamp to the nearest pixel.
+// translucent images are blurred premultiplied, see gaussian_blur_in_place
+#[wasm_bindgen]
+pub fn gaussian_blur(image_data: &ImageData, sigma: f32) -> Result<ImageData, JsValue> {
+    gaussian_blur_checked(image_data, sigma, None)
+}
+
+// gaussian_blur that stops with a CANCELLED error once `cancel` is tripped
+#[wasm_bindgen]
+pub fn gaussian_blur_cancellable(image_data: &ImageData, sigma: f32, cancel: &CancellationToken) -> Result<ImageData, JsValue> {
+    gaussian_blur_checked(image_data, sigma, Some(cancel))
+}
+
+fn gaussian_blur_checked(image_data: &ImageData, sigma: f32, cancel: Option<&CancellationToken>) -> Result<ImageData, JsValue> {
+    if !(sigma.is_finite() && sigma > 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "sigma must be a positive number"));
+    }
+
+    let mut data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut scratch = vec![0u8; data.len()];
+    gaussian_blur_in_place_cancellable(&mut data, &mut scratch, width, height, sigma, BorderMode::Clamp, cancel);
+    cancel::check(cancel)?;
+
     ImageData::new_with_u8_clamped_array_and_sh(
-        wasm_bindgen::Clamped(&output),
+        wasm_bindgen::Clamped(&data),
         width as u32,
         height as u32,
     )
 }
 
-fn blur(image_data: &ImageData) -> Result<ImageData, JsValue> {
-    let data = image_data.data().0;
+// box average over a (2 * radius + 1)^2 window on rgb, alpha is kept.
+// borders clamp, radius 0 returns the image unchanged
+#[wasm_bindgen]
+pub fn mean_blur(image_data: &ImageData, radius: u32) -> Result<ImageData, JsValue> {
+    let mut data = image_data.data().0;
     let width = image_data.width() as usize;
     let height = image_data.height() as usize;
-    let mut out = vec![0u8; data.len()];
-    
+
+    if radius > 0 {
+        let mut scratch = vec![0u8; data.len()];
+        box_blur_in_place(&mut data, &mut scratch, width, height, box_radius(radius, width, height), BorderMode::Clamp);
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        width as u32,
+        height as u32,
+    )
+}
+
+// `input` must already be grayscale (blur_into or grayscale_into output), only channel 0 is read
+// border pixels are left fully transparent
+fn edges_into(
+    input: &[u8],
+    output: &mut [u8],
+    width: usize,
+    height: usize,
+    thresh: u8,
+    operator: EdgeOperator,
+) {
+    edges_into_cancellable(input, output, width, height, thresh, operator, None);
+}
+
+// stops early once `cancel` is tripped, the caller has to check the token and
+// throw the half written output away
+fn edges_into_cancellable(
+    input: &[u8],
+    output: &mut [u8],
+    width: usize,
+    height: usize,
+    thresh: u8,
+    operator: EdgeOperator,
+    cancel: Option<&CancellationToken>,
+) {
+    output.fill(0);
+    let scale = operator.scale();
+    let last = height.saturating_sub(1);
+
+    for_each_row(output, width, cancel, |y, row| {
+        if y == 0 || y >= last {
+            return;
+        }
+        for x in 1..width.saturating_sub(1) {
+            let (gx, gy) = gradient(input, width, x, y, operator);
+
+            let magnitude = (((gx * gx + gy * gy) as f64).sqrt() * scale).round().min(255.0) as u8;
+            let edge = if magnitude > thresh { 255 } else { 0 };
+
+            let idx = x * 4;
+            row[idx] = edge;
+            row[idx + 1] = edge;
+            row[idx + 2] = edge;
+            row[idx + 3] = 255;
+        }
+    });
+}
+
+// sobel + non-maximum suppression: a pixel only survives if its magnitude is a
+// local max along the gradient direction (quantized to 0/45/90/135 degrees)
+fn sobel_thin_into(input: &[u8], output: &mut [u8], width: usize, height: usize, thresh: u8) {
+    output.fill(0);
+
+    // squared magnitudes, unclamped so strong edges don't all tie at 255
+    let mut mag_sq = vec![0i32; width * height];
+    let mut direction = vec![0u8; width * height];
+
+    for y in 1..height.saturating_sub(1) {
+        for x in 1..width.saturating_sub(1) {
+            let (gx, gy) = gradient(input, width, x, y, EdgeOperator::Sobel);
+            mag_sq[y * width + x] = gx * gx + gy * gy;
+
+            let angle = (gy as f64).atan2(gx as f64).to_degrees().rem_euclid(180.0);
+            direction[y * width + x] = if !(22.5..157.5).contains(&angle) {
+                0 // horizontal gradient -> vertical edge
+            } else if angle < 67.5 {
+                1
+            } else if angle < 112.5 {
+                2
+            } else {
+                3
+            };
+        }
+    }
+
+    for y in 1..height.saturating_sub(1) {
+        for x in 1..width.saturating_sub(1) {
+            let i = y * width + x;
+            // neighbours on either side along the gradient (y grows downwards)
+            let (before, after) = match direction[i] {
+                0 => (i - 1, i + 1),
+                1 => (i - width - 1, i + width + 1),
+                2 => (i - width, i + width),
+                _ => (i - width + 1, i + width - 1),
+            };
+
+            // >= on one side and > on the other, so a two pixel plateau keeps exactly one
+            let is_max = mag_sq[i] >= mag_sq[before] && mag_sq[i] > mag_sq[after];
+            let magnitude = (mag_sq[i] as f64).sqrt().round().min(255.0) as u8;
+            let edge = if is_max && magnitude > thresh { 255 } else { 0 };
+
+            let idx = i * 4;
+            output[idx] = edge;
+            output[idx + 1] = edge;
+            output[idx + 2] = edge;
+            output[idx + 3] = 255;
+        }
+    }
+}
+
+// gradient at an interior pixel, reads channel 0 of a grayscale buffer
+// 3x3 kernels are centered on the pixel, roberts (2x2) is anchored at its top-left
+fn gradient(input: &[u8], width: usize, x: usize, y: usize, operator: EdgeOperator) -> (i32, i32) {
+    let (size, kernel_x, kernel_y) = operator.kernels();
+    let offset = if size == 3 { 1 } else { 0 };
+
+    let mut gx = 0i32;
+    let mut gy = 0i32;
+
+    for ky in 0..size {
+        for kx in 0..size {
+            let px = x + kx - offset;
+            let py = y + ky - offset;
+            let idx = (py * width + px) * 4;
+            let gray = input[idx] as i32; // already grayscale from blur
+
+            gx += gray * kernel_x[ky * size + kx];
+            gy += gray * kernel_y[ky * size + kx];
+        }
+    }
+
+    (gx, gy)
+}
+
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+use simd::{blur_into, grayscale_into};
+
+// grayscale + 3x3 gaussian in one pass, border pixels are left fully transparent
+#[cfg(not(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128")))]
+fn blur_into(data: &[u8], out: &mut [u8], width: usize, height: usize) {
+    out.fill(0);
+
     let kernel: [[i32; 3]; 3] = [[1, 2, 1], [2, 4, 2], [1, 2, 1]];
     let sum_k = 16;
 
-    // using gaussian kernel - good enough for edge prep 
-    for y in 1..height-1 {
-        for x in 1..width-1 {
+    // using gaussian kernel - good enough for edge prep
+    for y in 1..height.saturating_sub(1) {
+        for x in 1..width.saturating_sub(1) {
             let mut acc = 0i32;
-            
-            for ky in 0..3 {
-                for kx in 0..3 {
+
+            for (ky, row) in kernel.iter().enumerate() {
+                for (kx, weight) in row.iter().enumerate() {
                     let px = x + kx - 1;
                     let py = y + ky - 1;
                     let idx = (py * width + px) * 4;
@@ -79,10 +451,10 @@ fn blur(image_data: &ImageData) -> Result<ImageData, JsValue> {
                     let g = data[idx + 1] as i32;
                     let b = data[idx + 2] as i32;
                     let gray = ((r + g + b) as f64 / 3.0).round() as i32;
-                    acc += gray * kernel[ky][kx];
+                    acc += gray * weight;
                 }
             }
-            
+
             let g = (acc as f64 / sum_k as f64).round() as u8;
             let idx = (y * width + x) * 4;
             out[idx] = g;
@@ -91,10 +463,289 @@ fn blur(image_data: &ImageData) -> Result<ImageData, JsValue> {
             out[idx + 3] = 255;
         }
     }
-    
-    ImageData::new_with_u8_clamped_array_and_sh(
-        wasm_bindgen::Clamped(&out),
-        width as u32,
-        height as u32,
-    )
-}
\ No newline at end of file
+}
+
+// rgb average into all three channels, alpha 255 (same conversion blur_into uses)
+#[cfg(not(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128")))]
+fn grayscale_into(data: &[u8], out: &mut [u8]) {
+    for (src, dst) in data.chunks_exact(4).zip(out.chunks_exact_mut(4)) {
+        let gray = ((src[0] as i32 + src[1] as i32 + src[2] as i32) as f64 / 3.0).round() as u8;
+        dst[0] = gray;
+        dst[1] = gray;
+        dst[2] = gray;
+        dst[3] = 255;
+    }
+}
+
+// normalized 1D kernel, radius of 3 sigma covers >99% of the curve
+fn gaussian_kernel(sigma: f32) -> Vec<f32> {
+    let radius = ((sigma * 3.0).ceil() as usize).max(1);
+    let mut kernel: Vec<f32> = (0..=radius * 2)
+        .map(|i| {
+            let d = i as f32 - radius as f32;
+            (-(d * d) / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+
+    let sum: f32 = kernel.iter().sum();
+    for w in kernel.iter_mut() {
+        *w /= sum;
+    }
+    kernel
+}
+
+// horizontal pass data -> scratch, vertical pass scratch -> data
+// `scratch` must be the same length as `data`, its contents are overwritten.
+// with any alpha below 255 the blur runs on premultiplied color, otherwise
+// transparent pixels would bleed their rgb into the visible ones
+fn gaussian_blur_in_place(data: &mut [u8], scratch: &mut [u8], width: usize, height: usize, sigma: f32) {
+    gaussian_blur_in_place_cancellable(data, scratch, width, height, sigma, BorderMode::Clamp, None);
+}
+
+// `border` picks what the kernel reads past the edge (gaussian_blur_in_place
+// clamps). stops early once `cancel` is tripped, `data` is garbage then and the
+// caller has to check the token
+fn gaussian_blur_in_place_cancellable(
+    data: &mut [u8],
+    scratch: &mut [u8],
+    width: usize,
+    height: usize,
+    sigma: f32,
+    border: BorderMode,
+    cancel: Option<&CancellationToken>,
+) {
+    let kernel = gaussian_kernel(sigma);
+    let radius = (kernel.len() / 2) as isize;
+    let translucent = has_translucency(data);
+    if translucent {
+        premultiply_pixels(data);
+    }
+
+    for_each_row(scratch, width, cancel, |y, row| {
+        for x in 0..width {
+            let mut acc = [0f32; 4];
+            for (i, w) in kernel.iter().enumerate() {
+                // zero border: nothing to add
+                let Some(sx) = border.resolve(x as isize + i as isize - radius, width) else {
+                    continue;
+                };
+                let idx = (y * width + sx) * 4;
+                for c in 0..4 {
+                    acc[c] += data[idx + c] as f32 * w;
+                }
+            }
+            for c in 0..4 {
+                row[x * 4 + c] = acc[c].round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    });
+
+    let scratch = &*scratch;
+    for_each_row(data, width, cancel, |y, row| {
+        for x in 0..width {
+            let mut acc = [0f32; 4];
+            for (i, w) in kernel.iter().enumerate() {
+                let Some(sy) = border.resolve(y as isize + i as isize - radius, height) else {
+                    continue;
+                };
+                let idx = (sy * width + x) * 4;
+                for c in 0..4 {
+                    acc[c] += scratch[idx + c] as f32 * w;
+                }
+            }
+            for c in 0..4 {
+                row[x * 4 + c] = acc[c].round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    });
+
+    if translucent {
+        unpremultiply_pixels(data);
+    }
+}
+
+// calls `f(y, row)` for every RGBA row of `out`, in bands of cancel::CHECK_ROWS
+// rows. bands go to the thread pool when the `parallel` build has one running
+// (see wasm_src_common::parallel), otherwise top to bottom on this thread. `f`
+// only writes its own row, so both give the same bytes. once `cancel` is
+// tripped the remaining bands are skipped
+fn for_each_row(
+    out: &mut [u8],
+    width: usize,
+    cancel: Option<&CancellationToken>,
+    f: impl Fn(usize, &mut [u8]) + Sync + Send,
+) {
+    if width == 0 {
+        return;
+    }
+    let band = |(b, band): (usize, &mut [u8])| {
+        if cancel::is_cancelled(cancel) {
+            return;
+        }
+        for (i, row) in band.chunks_mut(width * 4).enumerate() {
+            f(b * cancel::CHECK_ROWS + i, row);
+        }
+    };
+
+    #[cfg(feature = "parallel")]
+    if wasm_src_common::parallel::pool_ready() {
+        use rayon::prelude::*;
+        out.par_chunks_mut(width * 4 * cancel::CHECK_ROWS).enumerate().for_each(band);
+        return;
+    }
+
+    out.chunks_mut(width * 4 * cancel::CHECK_ROWS).enumerate().for_each(band);
+}
+
+// mean_blur radius as passed to box_blur_in_place. a window wider than the
+// image only takes in more border pixels, and capping it at the image size
+// keeps the (2r + 1) * 255 window sums in box_pass far from overflowing u32
+// (and 2r + 1 itself from overflowing usize on wasm32)
+fn box_radius(radius: u32, width: usize, height: usize) -> usize {
+    (radius as usize).min(width.max(height))
+}
+
+// separable box blur on rgb, same data -> scratch -> data layout as
+// gaussian_blur_in_place. each pass keeps a running window sum, so the cost
+// doesn't depend on the radius. `border` picks what the window reads past the edge
+fn box_blur_in_place(data: &mut [u8], scratch: &mut [u8], width: usize, height: usize, radius: usize, border: BorderMode) {
+    // horizontal pass, then the same along columns. only rgb is written, so
+    // alpha in `data` is never touched
+    box_pass(data, scratch, width, radius, 4, width * 4, border);
+    box_pass(scratch, data, height, radius, width * 4, 4, border);
+}
+
+// blurs every line of `length` pixels in `src`. `step` is the byte distance
+// between neighbours in a line, `line_step` between the first pixels of two lines
+fn box_pass(src: &[u8], dst: &mut [u8], length: usize, radius: usize, step: usize, line_step: usize, border: BorderMode) {
+    if length == 0 {
+        return;
+    }
+    let lines = src.len() / 4 / length;
+    let size = (radius * 2 + 1) as u32;
+    // rgb at position i of a line, zero padding reads as black
+    let read = |line: usize, i: isize| match border.resolve(i, length) {
+        Some(i) => {
+            let idx = line * line_step + i * step;
+            [src[idx] as u32, src[idx + 1] as u32, src[idx + 2] as u32]
+        }
+        None => [0; 3],
+    };
+
+    for line in 0..lines {
+        let mut sums = [0u32; 3];
+        for i in -(radius as isize)..=radius as isize {
+            let px = read(line, i);
+            for c in 0..3 {
+                sums[c] += px[c];
+            }
+        }
+
+        for i in 0..length {
+            let idx = line * line_step + i * step;
+            for c in 0..3 {
+                dst[idx + c] = ((sums[c] + size / 2) / size) as u8;
+            }
+            // slide: drop the leftmost pixel, take in the next one
+            let out = read(line, i as isize - radius as isize);
+            let into = read(line, i as isize + radius as isize + 1);
+            for c in 0..3 {
+                sums[c] = sums[c] + into[c] - out[c];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noise(width: usize, height: usize) -> Vec<u8> {
+        let mut state = 12345u32;
+        (0..width * height * 4)
+            .map(|i| {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+                if i % 4 == 3 { 255 } else { (state >> 24) as u8 }
+            })
+            .collect()
+    }
+
+    // moves every row `shift` pixels to the right, wrapping around
+    fn roll(data: &[u8], width: usize, shift: usize) -> Vec<u8> {
+        let mut out = vec![0u8; data.len()];
+        for (src, dst) in data.chunks_exact(width * 4).zip(out.chunks_exact_mut(width * 4)) {
+            for x in 0..width {
+                let to = (x + shift) % width * 4;
+                dst[to..to + 4].copy_from_slice(&src[x * 4..x * 4 + 4]);
+            }
+        }
+        out
+    }
+
+    // with Wrap the left and right edges are neighbours, so blurring a
+    // tileable image has no seam: shifting before or after the blur is the same
+    fn assert_tileable(blur: impl Fn(&mut [u8], &mut [u8], usize, usize)) {
+        let (width, height) = (13, 7);
+        let input = noise(width, height);
+        for shift in [1, 5, 12] {
+            let mut blurred = input.clone();
+            let mut scratch = vec![0u8; input.len()];
+            blur(&mut blurred, &mut scratch, width, height);
+
+            let mut shifted = roll(&input, width, shift);
+            blur(&mut shifted, &mut scratch, width, height);
+            assert_eq!(shifted, roll(&blurred, width, shift), "shift {}", shift);
+        }
+    }
+
+    #[test]
+    fn wrap_box_blur_stays_tileable() {
+        assert_tileable(|data, scratch, width, height| box_blur_in_place(data, scratch, width, height, 3, BorderMode::Wrap));
+    }
+
+    #[test]
+    fn wrap_gaussian_blur_stays_tileable() {
+        assert_tileable(|data, scratch, width, height| {
+            gaussian_blur_in_place_cancellable(data, scratch, width, height, 2.0, BorderMode::Wrap, None)
+        });
+    }
+
+    // flat gray image, dark on the left of `split` and light from it on
+    fn vertical_step(width: usize, height: usize, split: usize) -> Vec<u8> {
+        let mut data = vec![0u8; width * height * 4];
+        for (i, px) in data.chunks_exact_mut(4).enumerate() {
+            let v = if i % width < split { 20 } else { 220 };
+            px.copy_from_slice(&[v, v, v, 255]);
+        }
+        data
+    }
+
+    #[test]
+    fn sobel_thin_step_is_one_pixel_wide() {
+        let (width, height) = (12, 8);
+        let input = vertical_step(width, height, 6);
+        let mut output = vec![0u8; input.len()];
+        sobel_thin_into(&input, &mut output, width, height, 100);
+
+        for y in 1..height - 1 {
+            let edges: Vec<usize> = (0..width).filter(|&x| output[(y * width + x) * 4] == 255).collect();
+            assert_eq!(edges.len(), 1, "row {} has edges at {:?}", y, edges);
+            assert!(edges[0] == 5 || edges[0] == 6, "row {} edge at {}", y, edges[0]);
+        }
+    }
+
+    #[test]
+    fn mean_blur_keeps_a_solid_image() {
+        let (width, height) = (7, 5);
+        let solid: Vec<u8> = (0..width * height).flat_map(|_| [40, 180, 220, 255]).collect();
+
+        for radius in [1, 2, 6, u32::MAX] {
+            for border in [BorderMode::Clamp, BorderMode::Reflect, BorderMode::Wrap] {
+                let mut data = solid.clone();
+                let mut scratch = vec![0u8; data.len()];
+                box_blur_in_place(&mut data, &mut scratch, width, height, box_radius(radius, width, height), border);
+                assert_eq!(data, solid, "radius {}", radius);
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+use wasm_src_common::linear::{decode_lut, encode};
+
+use crate::border::{gaussian_blur_border, BorderMode};
+use crate::{gaussian_blur, gaussian_kernel};
+
+// `gaussian_blur` that averages light instead of sRGB bytes (see
+// wasm_src_common::linear), so blurred edges between colors don't go dark.
+// works in floats throughout, linear light in 8 bits would band in the
+// shadows. color is premultiplied like in gaussian_blur, borders clamp.
+// linear = false is plain `gaussian_blur`
+#[wasm_bindgen]
+pub fn gaussian_blur_opts(image_data: &ImageData, sigma: f32, linear: bool) -> Result<ImageData, JsValue> {
+    if !linear {
+        return gaussian_blur(image_data, sigma);
+    }
+    gaussian_blur_linear_checked(image_data, sigma, BorderMode::Clamp)
+}
+
+// gaussian_blur_opts with a choice of border mode, linear = false is
+// `gaussian_blur_border`
+#[wasm_bindgen]
+pub fn gaussian_blur_opts_border(image_data: &ImageData, sigma: f32, linear: bool, border: u8) -> Result<ImageData, JsValue> {
+    if !linear {
+        return gaussian_blur_border(image_data, sigma, border);
+    }
+    gaussian_blur_linear_checked(image_data, sigma, BorderMode::from_u8(border)?)
+}
+
+fn gaussian_blur_linear_checked(image_data: &ImageData, sigma: f32, border: BorderMode) -> Result<ImageData, JsValue> {
+    if !(sigma.is_finite() && sigma > 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "sigma must be a positive number"));
+    }
+
+    let mut data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    gaussian_blur_linear(&mut data, width, height, sigma, border);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        width as u32,
+        height as u32,
+    )
+}
+
+fn gaussian_blur_linear(data: &mut [u8], width: usize, height: usize, sigma: f32, border: BorderMode) {
+    let lut = decode_lut();
+    // premultiplied linear rgb + alpha, all 0..1
+    let mut pixels: Vec<[f64; 4]> = data
+        .chunks_exact(4)
+        .map(|px| {
+            let a = px[3] as f64 / 255.0;
+            [lut[px[0] as usize] * a, lut[px[1] as usize] * a, lut[px[2] as usize] * a, a]
+        })
+        .collect();
+    let mut scratch = pixels.clone();
+
+    let kernel = gaussian_kernel(sigma);
+    let radius = kernel.len() / 2;
+    // i + k - radius for tap k is entry i + k
+    let columns = border.resolve_range(width, radius);
+    let rows = border.resolve_range(height, radius);
+    // one pass along rows or columns: `at(line, i)` is the i-th pixel of a line
+    let pass = |src: &[[f64; 4]], dst: &mut [[f64; 4]], lines: usize, taps: &[Option<usize>], at: &dyn Fn(usize, usize) -> usize| {
+        let length = taps.len().saturating_sub(2 * radius);
+        for line in 0..lines {
+            for i in 0..length {
+                let mut acc = [0f64; 4];
+                for (k, w) in kernel.iter().enumerate() {
+                    // zero padding is a transparent pixel
+                    let Some(j) = taps[i + k] else { continue };
+                    let px = src[at(line, j)];
+                    for c in 0..4 {
+                        acc[c] += px[c] * *w as f64;
+                    }
+                }
+                dst[at(line, i)] = acc;
+            }
+        }
+    };
+    pass(&pixels, &mut scratch, height, &columns, &|y, x| y * width + x);
+    pass(&scratch, &mut pixels, width, &rows, &|x, y| y * width + x);
+
+    for (px, &[r, g, b, a]) in data.chunks_exact_mut(4).zip(&pixels) {
+        if a <= 0.0 {
+            px.fill(0);
+            continue;
+        }
+        px[0] = encode(r / a);
+        px[1] = encode(g / a);
+        px[2] = encode(b / a);
+        px[3] = (a * 255.0).round().clamp(0.0, 255.0) as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gaussian_blur_in_place_cancellable;
+
+    #[test]
+    fn linear_blur_keeps_red_green_edges_bright() {
+        // alternating red and green columns, fully blurred they should mix to yellow
+        let (width, height) = (16, 4);
+        let mut srgb: Vec<u8> = (0..width * height)
+            .flat_map(|i| if i % 2 == 0 { [255, 0, 0, 255] } else { [0, 255, 0, 255] })
+            .collect();
+        let mut linear = srgb.clone();
+
+        let mut scratch = vec![0u8; srgb.len()];
+        gaussian_blur_in_place_cancellable(&mut srgb, &mut scratch, width, height, 2.0, BorderMode::Clamp, None);
+        gaussian_blur_linear(&mut linear, width, height, 2.0, BorderMode::Clamp);
+
+        // averaging sRGB bytes gives a muddy ~128, averaging light gives ~188
+        let center = (2 * width + width / 2) * 4;
+        for c in 0..2 {
+            assert!(
+                linear[center + c] as i32 - srgb[center + c] as i32 > 40,
+                "linear {:?} vs srgb {:?}",
+                &linear[center..center + 4],
+                &srgb[center..center + 4]
+            );
+        }
+        assert_eq!(linear[center + 3], 255);
+    }
+}
@@ -0,0 +1,114 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::{blur_into, edges_into, EdgeOperator};
+
+// draws the sobel edges in `color` (rgb) over the untouched source image
+// opacity is clamped to 0..1, thickness grows the lines by that many pixels on
+// each side (none/0 = edges exactly as detected)
+#[wasm_bindgen]
+pub fn edge_overlay(
+    image_data: &ImageData,
+    threshold: u8,
+    color: &[u8],
+    opacity: f32,
+    thickness: Option<u32>,
+) -> Result<ImageData, JsValue> {
+    if color.len() != 3 {
+        return Err(error(ErrorCode::InvalidLength, "edge color must be an [r, g, b] triple"));
+    }
+    if !opacity.is_finite() {
+        return Err(error(ErrorCode::InvalidParameter, "opacity must be a number"));
+    }
+    let alpha = opacity.clamp(0.0, 1.0);
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let mut data = image_data.data().0;
+
+    let mut blurred = vec![0u8; data.len()];
+    blur_into(&data, &mut blurred, width, height);
+    let mut edges = vec![0u8; data.len()];
+    edges_into(&blurred, &mut edges, width, height, threshold, EdgeOperator::Sobel);
+
+    let mut mask: Vec<bool> = edges.chunks_exact(4).map(|px| px[0] == 255).collect();
+    let radius = thickness.unwrap_or(0) as usize;
+    if radius > 0 {
+        mask = dilate(&mask, width, height, radius);
+    }
+
+    for (px, &is_edge) in data.chunks_exact_mut(4).zip(mask.iter()) {
+        if !is_edge {
+            continue;
+        }
+        for c in 0..3 {
+            let blended = px[c] as f32 * (1.0 - alpha) + color[c] as f32 * alpha;
+            px[c] = blended.round() as u8;
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&data),
+        width as u32,
+        height as u32,
+    )
+}
+
+// square dilation, done as a horizontal then a vertical max pass
+fn dilate(mask: &[bool], width: usize, height: usize, radius: usize) -> Vec<bool> {
+    let mut horizontal = vec![false; mask.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let from = x.saturating_sub(radius);
+            let to = (x + radius).min(width - 1);
+            horizontal[y * width + x] = mask[y * width + from..=y * width + to].iter().any(|&m| m);
+        }
+    }
+
+    let mut out = vec![false; mask.len()];
+    for y in 0..height {
+        let from = y.saturating_sub(radius);
+        let to = (y + radius).min(height - 1);
+        for x in 0..width {
+            out[y * width + x] = (from..=to).any(|sy| horizontal[sy * width + x]);
+        }
+    }
+    out
+}
+
+// cel-shading look: flat k-means colors with black sobel outlines on top.
+// edges come from the original image so the outlines keep its detail
+#[wasm_bindgen]
+pub fn cartoonify(image_data: &ImageData, k: usize, edge_thresh: u8) -> Result<ImageData, JsValue> {
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let data = image_data.data().0;
+
+    let mut output = vec![0u8; data.len()];
+    wasm_src_common::kmeans::quantize_into(&data, &mut output, k);
+
+    let mut blurred = vec![0u8; data.len()];
+    blur_into(&data, &mut blurred, width, height);
+    let mut edges = vec![0u8; data.len()];
+    edges_into(&blurred, &mut edges, width, height, edge_thresh, EdgeOperator::Sobel);
+
+    // the only new part: black wherever the edge map is 255
+    for (px, edge) in output.chunks_exact_mut(4).zip(edges.chunks_exact(4)) {
+        if edge[0] == 255 {
+            px[0] = 0;
+            px[1] = 0;
+            px[2] = 0;
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
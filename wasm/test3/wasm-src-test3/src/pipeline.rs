@@ -0,0 +1,110 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::{blur_into, edges_into, gaussian_blur_in_place, EdgeOperator};
+
+// Chains several filters into one WASM call so the image only crosses the
+// JS <-> WASM boundary twice, no matter how many steps there are.
+//
+//   Pipeline.new().blur(1.5).quantize(8).edges(120).run(imageData)
+//
+// every step reads the front buffer and leaves its result in the front buffer,
+// using the back buffer as its output/scratch space. the two buffers are
+// allocated once per run and swapped between steps
+enum Step {
+    Blur { sigma: f32 },
+    Quantize { k: usize },
+    Edges { threshold: u8 },
+}
+
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct Pipeline {
+    steps: Vec<Step>,
+}
+
+#[wasm_bindgen]
+impl Pipeline {
+    pub fn new() -> Pipeline {
+        Pipeline::default()
+    }
+
+    // gaussian blur on all channels
+    pub fn blur(mut self, sigma: f32) -> Pipeline {
+        self.steps.push(Step::Blur { sigma });
+        self
+    }
+
+    // k-means color quantization, same as test2's `quantize`
+    pub fn quantize(mut self, k: usize) -> Pipeline {
+        self.steps.push(Step::Quantize { k });
+        self
+    }
+
+    // sobel edge map, same as `edge_detection` with a custom threshold
+    pub fn edges(mut self, threshold: u8) -> Pipeline {
+        self.steps.push(Step::Edges { threshold });
+        self
+    }
+
+    pub fn run(&self, image_data: &ImageData) -> Result<ImageData, JsValue> {
+        self.validate()?;
+
+        let width = image_data.width() as usize;
+        let height = image_data.height() as usize;
+        let mut front = image_data.data().0;
+        let mut back = vec![0u8; front.len()];
+
+        self.run_buffers(&mut front, &mut back, width, height);
+
+        ImageData::new_with_u8_clamped_array_and_sh(
+            wasm_bindgen::Clamped(&front),
+            width as u32,
+            height as u32,
+        )
+    }
+}
+
+impl Pipeline {
+    // check everything up front so a bad step doesn't waste the earlier ones
+    pub(crate) fn validate(&self) -> Result<(), JsValue> {
+        for step in &self.steps {
+            match step {
+                Step::Blur { sigma } if !(sigma.is_finite() && *sigma > 0.0) => {
+                    return Err(error(ErrorCode::InvalidParameter, "blur sigma must be a positive number"));
+                }
+                Step::Quantize { k: 0 } => {
+                    return Err(error(ErrorCode::InvalidK, "quantize k must be at least 1"));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // true if every step only looks at a small neighbourhood, so running it on
+    // tiles gives the same result as the whole image. k-means is global
+    pub(crate) fn is_local(&self) -> bool {
+        self.steps.iter().all(|step| !matches!(step, Step::Quantize { .. }))
+    }
+
+    // result ends up in `front`, both buffers must be width * height * 4 long
+    pub(crate) fn run_buffers(&self, front: &mut Vec<u8>, back: &mut Vec<u8>, width: usize, height: usize) {
+        for step in &self.steps {
+            match *step {
+                Step::Blur { sigma } => {
+                    gaussian_blur_in_place(front, back, width, height, sigma);
+                }
+                Step::Quantize { k } => {
+                    wasm_src_common::kmeans::quantize_into(front, back, k);
+                    std::mem::swap(front, back);
+                }
+                Step::Edges { threshold } => {
+                    blur_into(front, back, width, height);
+                    edges_into(back, front, width, height, threshold, EdgeOperator::Sobel);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::gaussian_blur_in_place;
+
+// blur before every decimation, sigma 1 is the usual 5-tap binomial width and
+// keeps frequencies above the new nyquist limit from aliasing
+const PRE_BLUR_SIGMA: f32 = 1.0;
+
+struct Level {
+    width: usize,
+    height: usize,
+    data: Vec<u8>,
+}
+
+#[wasm_bindgen]
+pub struct PyramidResult {
+    gaussian: Vec<Level>,
+    laplacian: Option<Vec<Level>>,
+}
+
+#[wasm_bindgen]
+impl PyramidResult {
+    // number of levels actually built, level 0 is the original size
+    #[wasm_bindgen(getter)]
+    pub fn levels(&self) -> u32 {
+        self.gaussian.len() as u32
+    }
+
+    pub fn gaussian(&self, level: u32) -> Result<ImageData, JsValue> {
+        to_image(self.gaussian.get(level as usize))
+    }
+
+    // band-pass residual gaussian(level) - upsampled gaussian(level + 1) as
+    // 128 + difference per rgb channel, so mid gray = nothing lost. the last
+    // level is the coarsest gaussian itself. errors if the pyramid was built
+    // without residuals
+    pub fn laplacian(&self, level: u32) -> Result<ImageData, JsValue> {
+        let Some(laplacian) = &self.laplacian else {
+            return Err(error(ErrorCode::InvalidParameter, "pyramid was built without laplacian levels"));
+        };
+        to_image(laplacian.get(level as usize))
+    }
+}
+
+// gaussian pyramid: every level is the previous one blurred and then halved
+// (floor) in both directions. stops early once the smaller side gets to 1
+// pixel, so asking for too many levels just gives fewer. with_laplacian also
+// keeps the residual between each level and the next
+#[wasm_bindgen]
+pub fn build_pyramid(image_data: &ImageData, levels: u32, with_laplacian: bool) -> Result<PyramidResult, JsValue> {
+    if levels == 0 {
+        return Err(error(ErrorCode::InvalidParameter, "levels must be at least 1"));
+    }
+
+    let mut gaussian = vec![Level {
+        width: image_data.width() as usize,
+        height: image_data.height() as usize,
+        data: image_data.data().0,
+    }];
+
+    while gaussian.len() < levels as usize {
+        let last = &gaussian[gaussian.len() - 1];
+        if last.width.min(last.height) < 2 {
+            break;
+        }
+        gaussian.push(downsample(last));
+    }
+
+    let laplacian = with_laplacian.then(|| {
+        let mut residuals: Vec<Level> = gaussian.windows(2).map(|pair| residual(&pair[0], &pair[1])).collect();
+        let top = &gaussian[gaussian.len() - 1];
+        residuals.push(Level {
+            width: top.width,
+            height: top.height,
+            data: top.data.clone(),
+        });
+        residuals
+    });
+
+    Ok(PyramidResult { gaussian, laplacian })
+}
+
+fn to_image(level: Option<&Level>) -> Result<ImageData, JsValue> {
+    let Some(level) = level else {
+        return Err(error(ErrorCode::OutOfBounds, "pyramid level out of range"));
+    };
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&level.data),
+        level.width as u32,
+        level.height as u32,
+    )
+}
+
+// pre-blur, then keep every other pixel
+fn downsample(level: &Level) -> Level {
+    let (width, height) = (level.width, level.height);
+    let mut blurred = level.data.clone();
+    let mut scratch = vec![0u8; blurred.len()];
+    gaussian_blur_in_place(&mut blurred, &mut scratch, width, height, PRE_BLUR_SIGMA);
+
+    let (half_w, half_h) = (width / 2, height / 2);
+    let mut data = Vec::with_capacity(half_w * half_h * 4);
+    for y in 0..half_h {
+        for x in 0..half_w {
+            let src = (2 * y * width + 2 * x) * 4;
+            data.extend_from_slice(&blurred[src..src + 4]);
+        }
+    }
+    Level { width: half_w, height: half_h, data }
+}
+
+fn residual(fine: &Level, coarse: &Level) -> Level {
+    let (width, height) = (fine.width, fine.height);
+    let mut data = vec![0u8; fine.data.len()];
+
+    for y in 0..height {
+        for x in 0..width {
+            // fine pixel (x, y) sits at (x / 2, y / 2) on the coarse grid
+            let up = sample_bilinear(coarse, x as f64 / 2.0, y as f64 / 2.0);
+            let idx = (y * width + x) * 4;
+            for c in 0..3 {
+                data[idx + c] = (128.0 + fine.data[idx + c] as f64 - up[c]).round().clamp(0.0, 255.0) as u8;
+            }
+            data[idx + 3] = 255;
+        }
+    }
+    Level { width, height, data }
+}
+
+// out of range coordinates clamp to the edge
+fn sample_bilinear(level: &Level, x: f64, y: f64) -> [f64; 3] {
+    let (width, height) = (level.width, level.height);
+    let x = x.clamp(0.0, (width - 1) as f64);
+    let y = y.clamp(0.0, (height - 1) as f64);
+    let x0 = x.floor() as usize;
+    let y0 = y.floor() as usize;
+    let x1 = (x0 + 1).min(width - 1);
+    let y1 = (y0 + 1).min(height - 1);
+    let fx = x - x0 as f64;
+    let fy = y - y0 as f64;
+
+    let mut px = [0f64; 3];
+    for (c, value) in px.iter_mut().enumerate() {
+        let p00 = level.data[(y0 * width + x0) * 4 + c] as f64;
+        let p10 = level.data[(y0 * width + x1) * 4 + c] as f64;
+        let p01 = level.data[(y1 * width + x0) * 4 + c] as f64;
+        let p11 = level.data[(y1 * width + x1) * 4 + c] as f64;
+        let top = p00 + (p10 - p00) * fx;
+        let bottom = p01 + (p11 - p01) * fx;
+        *value = top + (bottom - top) * fy;
+    }
+    px
+}
@@ -0,0 +1,159 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::{gradient, EdgeOperator};
+
+// content-aware resize: keeps removing the connected top-to-bottom path of
+// pixels (one per row, each within one column of the one above) with the least
+// sobel energy, so flat areas shrink and edges survive. energy is recomputed
+// after every seam. only narrows, target_width must be 1..=width
+#[wasm_bindgen]
+pub fn seam_carve(image_data: &ImageData, target_width: u32) -> Result<ImageData, JsValue> {
+    if target_width == 0 || target_width > image_data.width() {
+        return Err(error(ErrorCode::InvalidDimensions, "target_width must be between 1 and the image width"));
+    }
+
+    let height = image_data.height() as usize;
+    let mut carver = Carver::new(image_data.data().0, image_data.width() as usize, height);
+    while carver.width > target_width as usize {
+        carver.remove_seam();
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&carver.pixels),
+        target_width,
+        height as u32,
+    )
+}
+
+// the first `count` seams seam_carve would remove, drawn in red on the
+// original instead of removed. count must leave at least one column
+#[wasm_bindgen]
+pub fn visualize_seams(image_data: &ImageData, count: u32) -> Result<ImageData, JsValue> {
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    if count as usize >= width.max(1) {
+        return Err(error(ErrorCode::InvalidParameter, "count must be less than the image width"));
+    }
+
+    let mut output = image_data.data().0;
+    let mut carver = Carver::new(output.clone(), width, height);
+    for _ in 0..count {
+        for (y, x) in carver.remove_seam().into_iter().enumerate() {
+            let idx = (y * width + x) * 4;
+            output[idx..idx + 4].copy_from_slice(&[255, 0, 0, 255]);
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+struct Carver {
+    pixels: Vec<u8>,
+    // original column of every remaining pixel, for visualize_seams
+    origin: Vec<usize>,
+    width: usize,
+    height: usize,
+}
+
+impl Carver {
+    fn new(pixels: Vec<u8>, width: usize, height: usize) -> Carver {
+        let origin = (0..height).flat_map(|_| 0..width).collect();
+        Carver { pixels, origin, width, height }
+    }
+
+    // drops the cheapest seam, returns the original column it had in every row
+    fn remove_seam(&mut self) -> Vec<usize> {
+        let seam = self.find_seam();
+        let (width, height) = (self.width, self.height);
+
+        let mut removed = Vec::with_capacity(height);
+        // compact in place, rows only ever move left
+        let mut write = 0;
+        for (y, &skip) in seam.iter().enumerate() {
+            for x in 0..width {
+                let read = y * width + x;
+                if x == skip {
+                    removed.push(self.origin[read]);
+                    continue;
+                }
+                self.pixels.copy_within(read * 4..read * 4 + 4, write * 4);
+                self.origin[write] = self.origin[read];
+                write += 1;
+            }
+        }
+        self.pixels.truncate(write * 4);
+        self.origin.truncate(write);
+        self.width -= 1;
+        removed
+    }
+
+    // column of the minimum seam in every row
+    fn find_seam(&self) -> Vec<usize> {
+        let (width, height) = (self.width, self.height);
+        if height == 0 {
+            return Vec::new();
+        }
+        let energy = self.energy();
+
+        // cost[i] = cheapest seam from the top row down to pixel i
+        let mut cost = energy;
+        for y in 1..height {
+            for x in 0..width {
+                let above = (y - 1) * width;
+                let mut best = cost[above + x];
+                if x > 0 {
+                    best = best.min(cost[above + x - 1]);
+                }
+                if x + 1 < width {
+                    best = best.min(cost[above + x + 1]);
+                }
+                cost[y * width + x] += best;
+            }
+        }
+
+        // walk back up from the cheapest bottom pixel, leftmost on ties
+        let mut seam = vec![0; height];
+        let last = (height - 1) * width;
+        seam[height - 1] = (0..width).min_by_key(|&x| cost[last + x]).unwrap_or(0);
+        for y in (0..height - 1).rev() {
+            let below = seam[y + 1];
+            let from = below.saturating_sub(1);
+            let to = (below + 1).min(width - 1);
+            seam[y] = (from..=to).min_by_key(|&x| cost[y * width + x]).unwrap_or(below);
+        }
+        seam
+    }
+
+    // |gx| + |gy| sobel on the grayscale image. `gradient` only works on
+    // interior pixels, so it runs on a copy padded by one repeated pixel on
+    // every side and the borders get real energy too
+    fn energy(&self) -> Vec<u32> {
+        let (width, height) = (self.width, self.height);
+        let padded_width = width + 2;
+        let mut padded = vec![0u8; padded_width * (height + 2) * 4];
+        for py in 0..height + 2 {
+            let y = py.saturating_sub(1).min(height - 1);
+            for px in 0..padded_width {
+                let x = px.saturating_sub(1).min(width - 1);
+                let src = (y * width + x) * 4;
+                let gray = (self.pixels[src] as u32 + self.pixels[src + 1] as u32 + self.pixels[src + 2] as u32) / 3;
+                padded[(py * padded_width + px) * 4] = gray as u8;
+            }
+        }
+
+        let mut energy = vec![0u32; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                let (gx, gy) = gradient(&padded, padded_width, x + 1, y + 1, EdgeOperator::Sobel);
+                energy[y * width + x] = gx.unsigned_abs() + gy.unsigned_abs();
+            }
+        }
+        energy
+    }
+}
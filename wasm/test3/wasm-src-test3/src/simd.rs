@@ -0,0 +1,107 @@
+// simd128 versions of the grayscale and edge-prep blur loops. they're built
+// with the crate's `simd` feature (which also switches on the shared k-means
+// scan and the `simd_supported` probe in wasm_src_common) and
+// RUSTFLAGS="-C target-feature=+simd128". output is byte for byte the same
+// as the scalar functions in lib.rs:
+//   round((r + g + b) / 3)    == (r + g + b + 1) / 3, a sum never ends in .5
+//   round(acc / 16), acc >= 0 == (acc + 8) >> 4
+// and the integer divide by 3 is the usual multiply-shift, exact below 2^16
+
+use std::arch::wasm32::*;
+
+const DIV3_MUL: u32 = 0xAAAB;
+const DIV3_SHIFT: u32 = 17;
+
+// 4 pixels as u32 lanes (r in the low byte) -> their gray values
+fn gray_lanes(px: v128) -> v128 {
+    let byte = u32x4_splat(0xFF);
+    let r = v128_and(px, byte);
+    let g = v128_and(u32x4_shr(px, 8), byte);
+    let b = v128_and(u32x4_shr(px, 16), byte);
+    let sum = i32x4_add(i32x4_add(r, g), i32x4_add(b, u32x4_splat(1)));
+    u32x4_shr(i32x4_mul(sum, u32x4_splat(DIV3_MUL)), DIV3_SHIFT)
+}
+
+// gray lanes -> opaque gray pixels
+fn gray_pixels(gray: v128) -> v128 {
+    v128_or(i32x4_mul(gray, u32x4_splat(0x0001_0101)), u32x4_splat(0xFF00_0000))
+}
+
+fn gray_scalar(px: &[u8]) -> u32 {
+    (px[0] as u32 + px[1] as u32 + px[2] as u32 + 1) / 3
+}
+
+pub(crate) fn grayscale_into(data: &[u8], out: &mut [u8]) {
+    let mut src = data.chunks_exact(16);
+    let mut dst = out.chunks_exact_mut(16);
+    for (s, d) in (&mut src).zip(&mut dst) {
+        // SAFETY: both chunks are exactly 16 bytes, wasm loads/stores don't need alignment
+        unsafe {
+            let px = v128_load(s.as_ptr() as *const v128);
+            v128_store(d.as_mut_ptr() as *mut v128, gray_pixels(gray_lanes(px)));
+        }
+    }
+
+    for (s, d) in src.remainder().chunks_exact(4).zip(dst.into_remainder().chunks_exact_mut(4)) {
+        let gray = gray_scalar(s) as u8;
+        d.copy_from_slice(&[gray, gray, gray, 255]);
+    }
+}
+
+pub(crate) fn blur_into(data: &[u8], out: &mut [u8], width: usize, height: usize) {
+    out.fill(0);
+    if width < 3 || height < 3 {
+        return;
+    }
+
+    // the scalar version converts each neighbour on the fly, a gray plane
+    // up front gives the same numbers and lets the kernel load 4 at a time
+    let mut gray = vec![0u32; width * height];
+    let mut pixels = data.chunks_exact(16);
+    let mut lanes = gray.chunks_exact_mut(4);
+    for (s, g) in (&mut pixels).zip(&mut lanes) {
+        // SAFETY: 16 bytes in, 4 u32 out, unaligned access is fine on wasm
+        unsafe {
+            let px = v128_load(s.as_ptr() as *const v128);
+            v128_store(g.as_mut_ptr() as *mut v128, gray_lanes(px));
+        }
+    }
+    for (s, g) in pixels.remainder().chunks_exact(4).zip(lanes.into_remainder()) {
+        *g = gray_scalar(s);
+    }
+
+    // 1 2 1 / 2 4 2 / 1 2 1, output x..x + 4 reads gray x - 1..x + 5 per row
+    let load = |i: usize| {
+        // SAFETY: callers keep i + 4 <= gray.len()
+        unsafe { v128_load(gray.as_ptr().add(i) as *const v128) }
+    };
+    for y in 1..height - 1 {
+        let mut x = 1;
+        while x + 4 < width {
+            let mut acc = u32x4_splat(0);
+            for (dy, weights) in [(0, [1, 2, 1]), (1, [2, 4, 2]), (2, [1, 2, 1])] {
+                let row = (y + dy - 1) * width + x - 1;
+                for (dx, w) in weights.into_iter().enumerate() {
+                    acc = i32x4_add(acc, i32x4_mul(load(row + dx), u32x4_splat(w)));
+                }
+            }
+            let g = u32x4_shr(i32x4_add(acc, u32x4_splat(8)), 4);
+            // SAFETY: pixels x..x + 4 of row y are inside `out`
+            unsafe { v128_store(out.as_mut_ptr().add((y * width + x) * 4) as *mut v128, gray_pixels(g)) };
+            x += 4;
+        }
+
+        for x in x..width - 1 {
+            let mut acc = 0;
+            for (dy, weights) in [(0, [1, 2, 1]), (1, [2, 4, 2]), (2, [1, 2, 1])] {
+                let row = (y + dy - 1) * width + x - 1;
+                for (dx, w) in weights.into_iter().enumerate() {
+                    acc += gray[row + dx] * w;
+                }
+            }
+            let g = ((acc + 8) >> 4) as u8;
+            let idx = (y * width + x) * 4;
+            out[idx..idx + 4].copy_from_slice(&[g, g, g, 255]);
+        }
+    }
+}
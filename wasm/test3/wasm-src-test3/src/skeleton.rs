@@ -0,0 +1,84 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+
+use crate::distance::foreground_mask;
+
+// zhang-suen thinning: peels boundary pixels off the foreground (non-black)
+// until every shape is a 1 pixel wide skeleton. output is white skeleton on
+// black. feed it a binarized image, anti-aliased edges just count as foreground
+#[wasm_bindgen]
+pub fn skeletonize(image_data: &ImageData) -> Result<ImageData, JsValue> {
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut mask = foreground_mask(&image_data.data());
+    thin(&mut mask, width, height);
+
+    let mut output = vec![0u8; width * height * 4];
+    for (px, &on) in output.chunks_exact_mut(4).zip(&mask) {
+        let value = if on { 255 } else { 0 };
+        px[0] = value;
+        px[1] = value;
+        px[2] = value;
+        px[3] = 255;
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+fn thin(mask: &mut [bool], width: usize, height: usize) {
+    let mut remove = Vec::new();
+    loop {
+        let mut changed = false;
+        // the two sub-iterations strip the south-east and north-west sides in
+        // turn, which keeps the skeleton centered
+        for first in [true, false] {
+            remove.clear();
+            for y in 0..height {
+                for x in 0..width {
+                    if mask[y * width + x] && deletable(mask, width, height, x, y, first) {
+                        remove.push(y * width + x);
+                    }
+                }
+            }
+            // deletions only apply once the whole pass has looked at the old mask
+            for &i in &remove {
+                mask[i] = false;
+            }
+            changed |= !remove.is_empty();
+        }
+        if !changed {
+            break;
+        }
+    }
+}
+
+fn deletable(mask: &[bool], width: usize, height: usize, x: usize, y: usize, first: bool) -> bool {
+    let at = |dx: isize, dy: isize| {
+        let nx = x as isize + dx;
+        let ny = y as isize + dy;
+        nx >= 0 && ny >= 0 && (nx as usize) < width && (ny as usize) < height && mask[ny as usize * width + nx as usize]
+    };
+    // p2..p9, clockwise from north. outside the image counts as background
+    let p = [at(0, -1), at(1, -1), at(1, 0), at(1, 1), at(0, 1), at(-1, 1), at(-1, 0), at(-1, -1)];
+    let [p2, _, p4, _, p6, _, p8, _] = p;
+
+    // b = foreground neighbours, a = 0 -> 1 transitions going around the ring
+    let b = p.iter().filter(|&&on| on).count();
+    let a = (0..8).filter(|&i| !p[i] && p[(i + 1) % 8]).count();
+    if !(2..=6).contains(&b) || a != 1 {
+        return false;
+    }
+
+    if first {
+        // p2*p4*p6 == 0 and p4*p6*p8 == 0
+        !(p4 && p6 && (p2 || p8))
+    } else {
+        // p2*p4*p8 == 0 and p2*p6*p8 == 0
+        !(p2 && p8 && (p4 || p6))
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::{blur_into, sobel_thin_into};
+
+// "download as SVG" for the demo. coordinates are in pixels, path data uses
+// relative commands after the first moveto to keep the string small
+
+// thinned sobel edges traced into polylines, then simplified with
+// Ramer-Douglas-Peucker (simplify_epsilon in pixels, 0 keeps every point).
+// lone edge pixels are dropped as noise
+#[wasm_bindgen]
+pub fn edges_to_svg(image_data: &ImageData, threshold: u8, simplify_epsilon: f32) -> Result<String, JsValue> {
+    if !(simplify_epsilon.is_finite() && simplify_epsilon >= 0.0) {
+        return Err(error(ErrorCode::InvalidParameter, "simplify_epsilon must be a non-negative number"));
+    }
+
+    let data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut blurred = vec![0u8; data.len()];
+    blur_into(&data, &mut blurred, width, height);
+    let mut thin = vec![0u8; data.len()];
+    sobel_thin_into(&blurred, &mut thin, width, height, threshold);
+
+    let edge: Vec<bool> = thin.chunks_exact(4).map(|px| px[0] == 255).collect();
+
+    let mut d = String::new();
+    for (points, closed) in trace_chains(&edge, width, height) {
+        let points = simplify(&points, simplify_epsilon);
+        // pixel centers, so strokes sit on the pixels they came from
+        let _ = write!(d, "M{}.5 {}.5l", points[0].0, points[0].1);
+        for pair in points.windows(2) {
+            let _ = write!(d, "{} {} ", pair[1].0 - pair[0].0, pair[1].1 - pair[0].1);
+        }
+        d.pop();
+        if closed {
+            d.push('z');
+        }
+    }
+
+    let mut svg = svg_open(width, height);
+    let _ = write!(
+        svg,
+        r#"<path fill="none" stroke="black" stroke-linecap="round" stroke-linejoin="round" d="{d}"/>"#
+    );
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+// k-means quantize (same as test2's `quantize`), then one filled path per
+// palette color built from the pixel-edge outlines of its regions. outlines go
+// clockwise and holes counter-clockwise, so the default nonzero fill is right
+#[wasm_bindgen]
+pub fn quantized_to_svg(image_data: &ImageData, k: usize) -> Result<String, JsValue> {
+    if k == 0 {
+        return Err(error(ErrorCode::InvalidK, "k must be at least 1"));
+    }
+
+    let data = image_data.data().0;
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+
+    let mut quantized = vec![0u8; data.len()];
+    wasm_src_common::kmeans::quantize_into(&data, &mut quantized, k);
+    let colors: Vec<u32> = quantized
+        .chunks_exact(4)
+        .map(|px| (px[0] as u32) << 16 | (px[1] as u32) << 8 | px[2] as u32)
+        .collect();
+
+    let mut palette: Vec<u32> = colors.clone();
+    palette.sort_unstable();
+    palette.dedup();
+
+    // crispEdges avoids hairline seams between neighbouring regions
+    let mut svg = svg_open(width, height);
+    svg.insert_str(svg.len() - 1, r#" shape-rendering="crispEdges""#);
+    for color in palette {
+        let _ = write!(svg, r##"<path fill="#{color:06x}" d="{}"/>"##, region_outlines(&colors, width, height, color));
+    }
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+fn svg_open(width: usize, height: usize) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    )
+}
+
+// follows 8-connected edge pixels into chains. endpoints are used as starting
+// points first so open lines come out in one piece, whatever is left after
+// that are closed loops
+fn trace_chains(edge: &[bool], width: usize, height: usize) -> Vec<(Vec<(i32, i32)>, bool)> {
+    // 4-neighbours first so chains prefer straight steps over diagonals
+    const OFFSETS: [(i32, i32); 8] = [(1, 0), (0, 1), (-1, 0), (0, -1), (1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+    let at = |x: i32, y: i32| -> Option<usize> {
+        if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+            None
+        } else {
+            Some(y as usize * width + x as usize)
+        }
+    };
+    let neighbours = |i: usize| {
+        let (x, y) = ((i % width) as i32, (i / width) as i32);
+        OFFSETS.iter().filter_map(move |&(dx, dy)| at(x + dx, y + dy))
+    };
+
+    let mut visited = vec![false; edge.len()];
+    let mut chains = Vec::new();
+
+    let endpoints: Vec<usize> = (0..edge.len())
+        .filter(|&i| edge[i] && neighbours(i).filter(|&n| edge[n]).count() == 1)
+        .collect();
+    let starts = endpoints.into_iter().chain(0..edge.len());
+
+    for start in starts {
+        if !edge[start] || visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut chain = vec![start];
+        let mut current = start;
+        while let Some(next) = neighbours(current).find(|&n| edge[n] && !visited[n]) {
+            visited[next] = true;
+            chain.push(next);
+            current = next;
+        }
+
+        if chain.len() < 2 {
+            continue;
+        }
+        let closed = chain.len() > 2 && neighbours(current).any(|n| n == start);
+        let points = chain.iter().map(|&i| ((i % width) as i32, (i / width) as i32)).collect();
+        chains.push((points, closed));
+    }
+
+    chains
+}
+
+// Ramer-Douglas-Peucker with an explicit stack, long chains would recurse deep
+fn simplify(points: &[(i32, i32)], epsilon: f32) -> Vec<(i32, i32)> {
+    if points.len() < 3 || epsilon == 0.0 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut stack = vec![(0, points.len() - 1)];
+
+    while let Some((first, last)) = stack.pop() {
+        let (ax, ay) = (points[first].0 as f32, points[first].1 as f32);
+        let (bx, by) = (points[last].0 as f32, points[last].1 as f32);
+        let (dx, dy) = (bx - ax, by - ay);
+        let length = dx.hypot(dy);
+
+        let mut farthest = (0f32, first);
+        for (i, &(px, py)) in points.iter().enumerate().take(last).skip(first + 1) {
+            let (px, py) = (px as f32 - ax, py as f32 - ay);
+            // distance to the line, or to the start point when the ends coincide
+            let distance = if length > 0.0 { (px * dy - py * dx).abs() / length } else { px.hypot(py) };
+            if distance > farthest.0 {
+                farthest = (distance, i);
+            }
+        }
+
+        if farthest.0 > epsilon {
+            keep[farthest.1] = true;
+            stack.push((first, farthest.1));
+            stack.push((farthest.1, last));
+        }
+    }
+
+    points.iter().zip(keep).filter(|(_, k)| *k).map(|(p, _)| *p).collect()
+}
+
+// path data for every pixel of `color`. each pixel contributes the sides that
+// border a different color (or the image edge), walked clockwise, and the sides
+// are chained back into closed loops at the grid vertices
+fn region_outlines(colors: &[u32], width: usize, height: usize, color: u32) -> String {
+    let vertex = |x: usize, y: usize| y * (width + 1) + x;
+    let differs = |x: isize, y: isize| {
+        x < 0 || y < 0 || x >= width as isize || y >= height as isize || colors[y as usize * width + x as usize] != color
+    };
+
+    // vertex -> vertices reachable by an unused side. a vertex can have two
+    // when regions touch diagonally
+    let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+    for y in 0..height {
+        for x in 0..width {
+            if colors[y * width + x] != color {
+                continue;
+            }
+            let (xi, yi) = (x as isize, y as isize);
+            let mut side = |from: usize, to: usize| outgoing.entry(from).or_default().push(to);
+            if differs(xi, yi - 1) {
+                side(vertex(x, y), vertex(x + 1, y));
+            }
+            if differs(xi + 1, yi) {
+                side(vertex(x + 1, y), vertex(x + 1, y + 1));
+            }
+            if differs(xi, yi + 1) {
+                side(vertex(x + 1, y + 1), vertex(x, y + 1));
+            }
+            if differs(xi - 1, yi) {
+                side(vertex(x, y + 1), vertex(x, y));
+            }
+        }
+    }
+
+    // walk in a fixed order so the output is deterministic
+    let mut starts: Vec<usize> = outgoing.keys().copied().collect();
+    starts.sort_unstable();
+
+    let mut d = String::new();
+    let point = |v: usize| ((v % (width + 1)) as i64, (v / (width + 1)) as i64);
+    for start in starts {
+        while let Some(mut next) = outgoing.get_mut(&start).and_then(|to| to.pop()) {
+            let (sx, sy) = point(start);
+            let _ = write!(d, "M{sx} {sy}");
+
+            // merge straight runs into single h/v commands
+            let mut current = start;
+            let mut run = (0i64, 0i64);
+            loop {
+                let (cx, cy) = point(current);
+                let (nx, ny) = point(next);
+                let step = (nx - cx, ny - cy);
+                let same_direction = (run.0 != 0 && step.0 != 0 && run.0.signum() == step.0.signum())
+                    || (run.1 != 0 && step.1 != 0 && run.1.signum() == step.1.signum());
+                if same_direction {
+                    run = (run.0 + step.0, run.1 + step.1);
+                } else {
+                    write_run(&mut d, run);
+                    run = step;
+                }
+
+                current = next;
+                if current == start {
+                    break;
+                }
+                next = outgoing
+                    .get_mut(&current)
+                    .and_then(|to| to.pop())
+                    .expect("pixel outlines always close");
+            }
+            // the last run always ends back at the start, z draws it
+            d.push('z');
+        }
+    }
+    d
+}
+
+fn write_run(d: &mut String, run: (i64, i64)) {
+    if run.0 != 0 {
+        let _ = write!(d, "h{}", run.0);
+    } else if run.1 != 0 {
+        let _ = write!(d, "v{}", run.1);
+    }
+}
@@ -0,0 +1,179 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+// "find the sprite": slides `needle` over every position of `haystack` and
+// scores how well it fits. everything runs on grayscale to keep a 64x64 needle
+// on a 720p frame bearable, the window sums come from integral images so only
+// the cross term costs needle-size work per position
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MatchMethod {
+    SquaredDifference = 0, // sum of (h - n)^2, 0 is a perfect match, lower is better
+    Correlation = 1,       // zero-mean normalized cross-correlation, -1..1, higher is better
+}
+
+#[wasm_bindgen]
+pub struct MatchResult {
+    x: u32,
+    y: u32,
+    score: f64,
+    map_width: u32,
+    map_height: u32,
+    scores: Option<Vec<f32>>,
+}
+
+#[wasm_bindgen]
+impl MatchResult {
+    // top-left corner of the best match in the haystack
+    #[wasm_bindgen(getter)]
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+
+    // score map is (haystack - needle + 1) in each direction, one entry per
+    // top-left position
+    #[wasm_bindgen(getter)]
+    pub fn map_width(&self) -> u32 {
+        self.map_width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn map_height(&self) -> u32 {
+        self.map_height
+    }
+
+    // row-major Float32Array for a heatmap, undefined unless it was asked for
+    #[wasm_bindgen(getter)]
+    pub fn scores(&self) -> Option<Vec<f32>> {
+        self.scores.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn match_template(
+    haystack: &ImageData,
+    needle: &ImageData,
+    method: MatchMethod,
+    include_map: bool,
+) -> Result<MatchResult, JsValue> {
+    let (width, height) = (haystack.width() as usize, haystack.height() as usize);
+    let (nw, nh) = (needle.width() as usize, needle.height() as usize);
+    if nw == 0 || nh == 0 {
+        return Err(error(ErrorCode::InvalidDimensions, "needle must not be empty"));
+    }
+    if nw > width || nh > height {
+        return Err(error(ErrorCode::InvalidDimensions, "needle must not be larger than the haystack"));
+    }
+
+    let hay = gray(&haystack.data());
+    let tpl = gray(&needle.data());
+    let map_width = width - nw + 1;
+    let map_height = height - nh + 1;
+    let scores = score_map(&hay, width, height, &tpl, nw, nh, method);
+
+    // first best position wins ties, so a flat haystack reports (0, 0)
+    let mut best = 0;
+    for (i, &s) in scores.iter().enumerate() {
+        let better = match method {
+            MatchMethod::SquaredDifference => s < scores[best],
+            MatchMethod::Correlation => s > scores[best],
+        };
+        if better {
+            best = i;
+        }
+    }
+
+    Ok(MatchResult {
+        x: (best % map_width) as u32,
+        y: (best / map_width) as u32,
+        score: scores[best],
+        map_width: map_width as u32,
+        map_height: map_height as u32,
+        scores: include_map.then(|| scores.iter().map(|&s| s as f32).collect()),
+    })
+}
+
+// same (r + g + b) / 3 as grayscale_into, kept as floats
+fn gray(data: &[u8]) -> Vec<f32> {
+    data.chunks_exact(4)
+        .map(|px| (px[0] as f32 + px[1] as f32 + px[2] as f32) / 3.0)
+        .collect()
+}
+
+fn score_map(
+    hay: &[f32],
+    width: usize,
+    height: usize,
+    tpl: &[f32],
+    nw: usize,
+    nh: usize,
+    method: MatchMethod,
+) -> Vec<f64> {
+    let n = (nw * nh) as f64;
+    let tpl_sum: f64 = tpl.iter().map(|&v| v as f64).sum();
+    let tpl_sq: f64 = tpl.iter().map(|&v| v as f64 * v as f64).sum();
+    let tpl_var = tpl_sq - tpl_sum * tpl_sum / n;
+
+    // (width + 1) x (height + 1) with a zero row/column so lookups need no branches
+    let stride = width + 1;
+    let mut sums = vec![0f64; stride * (height + 1)];
+    let mut squares = vec![0f64; stride * (height + 1)];
+    for y in 0..height {
+        let (mut row_sum, mut row_sq) = (0f64, 0f64);
+        for x in 0..width {
+            let v = hay[y * width + x] as f64;
+            row_sum += v;
+            row_sq += v * v;
+            sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row_sum;
+            squares[(y + 1) * stride + x + 1] = squares[y * stride + x + 1] + row_sq;
+        }
+    }
+    let window = |table: &[f64], x: usize, y: usize| {
+        table[(y + nh) * stride + x + nw] + table[y * stride + x] - table[y * stride + x + nw] - table[(y + nh) * stride + x]
+    };
+
+    let (map_width, map_height) = (width - nw + 1, height - nh + 1);
+    let mut scores = Vec::with_capacity(map_width * map_height);
+    for y in 0..map_height {
+        for x in 0..map_width {
+            // sum of h * n, row by row so the inner loop is two plain slices
+            let mut cross = 0f64;
+            for (j, tpl_row) in tpl.chunks_exact(nw).enumerate() {
+                let start = (y + j) * width + x;
+                let row: f32 = hay[start..start + nw].iter().zip(tpl_row).map(|(a, b)| a * b).sum();
+                cross += row as f64;
+            }
+
+            let sum = window(&sums, x, y);
+            let sq = window(&squares, x, y);
+            let score = match method {
+                // expanded (h - n)^2, rounding can dip a hair below zero
+                MatchMethod::SquaredDifference => (sq - 2.0 * cross + tpl_sq).max(0.0),
+                MatchMethod::Correlation => {
+                    let var = sq - sum * sum / n;
+                    let denom = (var * tpl_var).sqrt();
+                    // a flat window or needle has no pattern to correlate
+                    if denom > 1e-6 {
+                        ((cross - sum * tpl_sum / n) / denom).clamp(-1.0, 1.0)
+                    } else {
+                        0.0
+                    }
+                }
+            };
+            scores.push(score);
+        }
+    }
+    scores
+}
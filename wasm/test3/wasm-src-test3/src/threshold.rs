@@ -0,0 +1,123 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::{gaussian_blur_in_place, grayscale_into};
+
+// local thresholding: a pixel is white if it's brighter than the mean of its
+// block_size x block_size neighbourhood minus `c`. handles shadows/uneven
+// lighting where one global threshold can't. block_size must be odd and >= 3
+// gaussian = true weights the neighbourhood with a gaussian instead of a flat mean
+#[wasm_bindgen]
+pub fn adaptive_threshold(
+    image_data: &ImageData,
+    block_size: u32,
+    c: i32,
+    gaussian: bool,
+) -> Result<ImageData, JsValue> {
+    if block_size < 3 || block_size.is_multiple_of(2) {
+        return Err(error(ErrorCode::InvalidParameter, "block_size must be odd and at least 3"));
+    }
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let output = adaptive_threshold_pixels(&image_data.data(), width, height, block_size, c, gaussian);
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}
+
+fn adaptive_threshold_pixels(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    block_size: u32,
+    c: i32,
+    gaussian: bool,
+) -> Vec<u8> {
+    let mut gray = vec![0u8; data.len()];
+    grayscale_into(data, &mut gray);
+
+    let local_mean: Vec<f64> = if gaussian {
+        // same block size -> sigma rule opencv uses
+        let sigma = 0.3 * ((block_size as f32 - 1.0) * 0.5 - 1.0) + 0.8;
+        let mut weighted = gray.clone();
+        let mut scratch = vec![0u8; data.len()];
+        gaussian_blur_in_place(&mut weighted, &mut scratch, width, height, sigma);
+        weighted.chunks_exact(4).map(|px| px[0] as f64).collect()
+    } else {
+        box_means(&gray, width, height, (block_size / 2) as usize)
+    };
+
+    let mut output = vec![0u8; data.len()];
+    for (i, mean) in local_mean.iter().enumerate() {
+        let value = if gray[i * 4] as f64 > mean - c as f64 { 255 } else { 0 };
+        let idx = i * 4;
+        output[idx] = value;
+        output[idx + 1] = value;
+        output[idx + 2] = value;
+        output[idx + 3] = 255;
+    }
+    output
+}
+
+// mean of the window around every pixel via an integral image, O(1) per pixel
+// windows are cut off at the borders and only count pixels inside the image
+fn box_means(gray: &[u8], width: usize, height: usize, radius: usize) -> Vec<f64> {
+    // (width + 1) x (height + 1) with a zero row/column so lookups need no branches
+    let stride = width + 1;
+    let mut integral = vec![0u64; stride * (height + 1)];
+    for y in 0..height {
+        let mut row_sum = 0u64;
+        for x in 0..width {
+            row_sum += gray[(y * width + x) * 4] as u64;
+            integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row_sum;
+        }
+    }
+
+    let mut means = vec![0f64; width * height];
+    for y in 0..height {
+        let y0 = y.saturating_sub(radius);
+        let y1 = (y + radius + 1).min(height);
+        for x in 0..width {
+            let x0 = x.saturating_sub(radius);
+            let x1 = (x + radius + 1).min(width);
+            let sum = integral[y1 * stride + x1] + integral[y0 * stride + x0]
+                - integral[y0 * stride + x1]
+                - integral[y1 * stride + x0];
+            let count = ((x1 - x0) * (y1 - y0)) as f64;
+            means[y * width + x] = sum as f64 / count;
+        }
+    }
+    means
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_a_step_under_uneven_lighting() {
+        // lighting ramps from 0 to 190 left to right and the step adds 40 from
+        // x = 10 on, only the local contrast at the step should come out
+        let (width, height) = (20, 3);
+        let data: Vec<u8> = (0..width * height)
+            .flat_map(|i| {
+                let x = i % width;
+                let v = (x * 10 + if x >= 10 { 40 } else { 0 }) as u8;
+                [v, v, v, 255]
+            })
+            .collect();
+
+        for gaussian in [false, true] {
+            let output = adaptive_threshold_pixels(&data, width, height, 5, 0, gaussian);
+            for y in 0..height {
+                let at = |x: usize| output[(y * width + x) * 4];
+                assert_eq!([at(8), at(9), at(10), at(11)], [0, 0, 255, 255], "gaussian = {}", gaussian);
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+use wasm_src_common::error::{error, ErrorCode};
+
+use crate::pipeline::Pipeline;
+
+// Runs a pipeline tile by tile so the working buffers only ever hold one tile
+// (plus overlap) instead of the whole image, which keeps memory flat on huge
+// images.
+//
+// Only local (neighbourhood) steps are tile-safe: blur and edges. quantize
+// needs the whole image's colors and is rejected.
+//
+// Each tile is processed with `overlap` extra pixels on every side and only its
+// core is written back. seams disappear once overlap covers how far the
+// pipeline reaches: ceil(3 * sigma) per blur step plus 2 per edges step
+#[wasm_bindgen]
+pub fn process_tiled(
+    image_data: &ImageData,
+    op: &Pipeline,
+    tile_size: u32,
+    overlap: u32,
+) -> Result<ImageData, JsValue> {
+    op.validate()?;
+    if !op.is_local() {
+        return Err(error(ErrorCode::InvalidParameter, "quantize is not tile-safe, run the pipeline on the whole image"));
+    }
+    if tile_size == 0 {
+        return Err(error(ErrorCode::InvalidParameter, "tile_size must be at least 1"));
+    }
+
+    let width = image_data.width() as usize;
+    let height = image_data.height() as usize;
+    let data = image_data.data().0;
+    let tile = tile_size as usize;
+    let overlap = overlap as usize;
+
+    let mut output = vec![0u8; data.len()];
+    // sized for the biggest padded tile, reused for every tile
+    let max_tile = (tile + 2 * overlap).min(width.max(height));
+    let mut front: Vec<u8> = Vec::with_capacity(max_tile * max_tile * 4);
+    let mut back: Vec<u8> = Vec::with_capacity(max_tile * max_tile * 4);
+
+    for core_y in (0..height).step_by(tile) {
+        for core_x in (0..width).step_by(tile) {
+            let core_w = tile.min(width - core_x);
+            let core_h = tile.min(height - core_y);
+
+            // padded region, clipped to the image
+            let x0 = core_x.saturating_sub(overlap);
+            let y0 = core_y.saturating_sub(overlap);
+            let x1 = (core_x + core_w + overlap).min(width);
+            let y1 = (core_y + core_h + overlap).min(height);
+            let (tile_w, tile_h) = (x1 - x0, y1 - y0);
+
+            front.clear();
+            for y in y0..y1 {
+                front.extend_from_slice(&data[(y * width + x0) * 4..(y * width + x1) * 4]);
+            }
+            back.clear();
+            back.resize(front.len(), 0);
+
+            op.run_buffers(&mut front, &mut back, tile_w, tile_h);
+
+            // copy just the core back
+            let off_x = core_x - x0;
+            let off_y = core_y - y0;
+            for row in 0..core_h {
+                let src = ((off_y + row) * tile_w + off_x) * 4;
+                let dst = ((core_y + row) * width + core_x) * 4;
+                output[dst..dst + core_w * 4].copy_from_slice(&front[src..src + core_w * 4]);
+            }
+        }
+    }
+
+    ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&output),
+        width as u32,
+        height as u32,
+    )
+}